@@ -0,0 +1,506 @@
+use crate::{Renderer, SamplerDescriptor, Texture, TextureDescriptor};
+use ahash::AHashMap as HashMap;
+use std::borrow::Cow;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AtlasImageId(u64);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtlasImage {
+    pub texture_id: imgui::TextureId,
+    pub uv_min: [f32; 2],
+    pub uv_max: [f32; 2],
+}
+
+struct Shelf {
+    y: u32,
+    height: u32,
+    x: u32,
+    image_count: u32,
+}
+
+struct PageAllocator {
+    size: u32,
+    shelves: Vec<Shelf>,
+}
+
+impl PageAllocator {
+    fn new(size: u32) -> Self {
+        PageAllocator {
+            size,
+            shelves: Vec::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.shelves.is_empty()
+    }
+
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(usize, u32, u32)> {
+        if width > self.size || height > self.size {
+            return None;
+        }
+
+        let best_shelf = self
+            .shelves
+            .iter()
+            .enumerate()
+            .filter(|(_, shelf)| shelf.height >= height && shelf.x + width <= self.size)
+            .min_by_key(|(_, shelf)| shelf.height - height)
+            .map(|(i, _)| i);
+        let shelf_index = match best_shelf {
+            Some(i) => i,
+            None => {
+                let y = self
+                    .shelves
+                    .last()
+                    .map_or(0, |shelf| shelf.y + shelf.height);
+                if y + height > self.size {
+                    return None;
+                }
+                self.shelves.push(Shelf {
+                    y,
+                    height,
+                    x: 0,
+                    image_count: 0,
+                });
+                self.shelves.len() - 1
+            }
+        };
+
+        let shelf = &mut self.shelves[shelf_index];
+        let x = shelf.x;
+        shelf.x += width;
+        shelf.image_count += 1;
+        Some((shelf_index, x, shelf.y))
+    }
+
+    fn deallocate(&mut self, shelf_index: usize) {
+        let shelf = &mut self.shelves[shelf_index];
+        shelf.image_count -= 1;
+        if shelf.image_count == 0 {
+            shelf.x = 0;
+        }
+        while self
+            .shelves
+            .last()
+            .is_some_and(|shelf| shelf.image_count == 0)
+        {
+            self.shelves.pop();
+        }
+    }
+}
+
+struct Page {
+    texture_id: imgui::TextureId,
+    allocator: PageAllocator,
+}
+
+#[derive(Clone, Copy)]
+struct Allocation {
+    page: usize,
+    shelf: usize,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+pub struct TextureAtlas {
+    label: Option<Cow<'static, str>>,
+    page_size: u32,
+    padding: u32,
+    format: wgpu::TextureFormat,
+    sampler_desc: SamplerDescriptor,
+    pages: Vec<Page>,
+    allocations: HashMap<AtlasImageId, Allocation>,
+    next_image_id: u64,
+}
+
+impl TextureAtlas {
+    #[must_use]
+    pub(crate) fn new(
+        label: Option<Cow<'static, str>>,
+        page_size: u32,
+        format: wgpu::TextureFormat,
+        sampler_desc: SamplerDescriptor,
+    ) -> Self {
+        assert!(
+            !format.is_compressed() && format.block_copy_size(None).is_some(),
+            "texture atlases are not supported for {format:?} textures"
+        );
+        TextureAtlas {
+            label,
+            page_size,
+            padding: 1,
+            format,
+            sampler_desc,
+            pages: Vec::new(),
+            allocations: HashMap::new(),
+            next_image_id: 0,
+        }
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    pub fn page_size(&self) -> u32 {
+        self.page_size
+    }
+
+    pub fn padding(&self) -> u32 {
+        self.padding
+    }
+
+    pub fn set_padding(&mut self, value: u32) {
+        self.padding = value;
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    pub fn page_texture_ids(&self) -> impl Iterator<Item = imgui::TextureId> + '_ {
+        self.pages.iter().map(|page| page.texture_id)
+    }
+
+    pub fn image_count(&self) -> usize {
+        self.allocations.len()
+    }
+
+    fn create_page_texture(&self, renderer: &Renderer) -> Texture {
+        Texture::Owned(renderer.create_owned_texture(
+            self.label.clone(),
+            TextureDescriptor {
+                width: self.page_size,
+                height: self.page_size,
+                depth_or_array_layers: 1,
+                mip_level_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: self.format,
+                usage: wgpu::TextureUsages::COPY_DST
+                    | wgpu::TextureUsages::COPY_SRC
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+            },
+            self.sampler_desc.clone(),
+        ))
+    }
+
+    fn allocate(pages: &mut [Page], width: u32, height: u32) -> Option<(usize, usize, u32, u32)> {
+        pages.iter_mut().enumerate().find_map(|(i, page)| {
+            page.allocator
+                .allocate(width, height)
+                .map(|(shelf, x, y)| (i, shelf, x, y))
+        })
+    }
+
+    pub fn insert(
+        &mut self,
+        renderer: &Renderer,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        width: u32,
+        height: u32,
+        data: &[u8],
+    ) -> Option<AtlasImageId> {
+        let texel_size = self.format.block_copy_size(None).unwrap();
+        let row_size = width as usize * texel_size as usize;
+        if width == 0 || height == 0 || data.len() < row_size * height as usize {
+            return None;
+        }
+
+        let padded_width = width + self.padding;
+        let padded_height = height + self.padding;
+        if padded_width > self.page_size || padded_height > self.page_size {
+            return None;
+        }
+
+        let (page, shelf, x, y) = match Self::allocate(&mut self.pages, padded_width, padded_height)
+        {
+            Some(allocation) => allocation,
+            None => {
+                let mut allocator = PageAllocator::new(self.page_size);
+                let (shelf, x, y) = allocator.allocate(padded_width, padded_height)?;
+                self.pages.push(Page {
+                    texture_id: renderer.add_texture(self.create_page_texture(renderer)),
+                    allocator,
+                });
+                (self.pages.len() - 1, shelf, x, y)
+            }
+        };
+
+        // The padding is cleared as well, so that texels of removed images can't bleed into this
+        // one when it's filtered
+        let padded_row_size = padded_width as usize * texel_size as usize;
+        let mut padded_data = vec![0; padded_row_size * padded_height as usize];
+        for (row, padded_row) in data
+            .chunks_exact(row_size)
+            .zip(padded_data.chunks_exact_mut(padded_row_size))
+        {
+            padded_row[..row_size].copy_from_slice(row);
+        }
+
+        let texture = renderer.texture(self.pages[page].texture_id);
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture.unwrap_owned_ref().texture(device),
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            &padded_data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_width * texel_size),
+                rows_per_image: None,
+            },
+            wgpu::Extent3d {
+                width: padded_width,
+                height: padded_height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        let id = AtlasImageId(self.next_image_id);
+        self.next_image_id += 1;
+        self.allocations.insert(
+            id,
+            Allocation {
+                page,
+                shelf,
+                x,
+                y,
+                width,
+                height,
+            },
+        );
+        Some(id)
+    }
+
+    pub fn image(&self, id: AtlasImageId) -> Option<AtlasImage> {
+        let allocation = self.allocations.get(&id)?;
+        let page_size = self.page_size as f32;
+        Some(AtlasImage {
+            texture_id: self.pages[allocation.page].texture_id,
+            uv_min: [
+                allocation.x as f32 / page_size,
+                allocation.y as f32 / page_size,
+            ],
+            uv_max: [
+                (allocation.x + allocation.width) as f32 / page_size,
+                (allocation.y + allocation.height) as f32 / page_size,
+            ],
+        })
+    }
+
+    pub fn remove(&mut self, id: AtlasImageId) -> bool {
+        match self.allocations.remove(&id) {
+            Some(allocation) => {
+                self.pages[allocation.page]
+                    .allocator
+                    .deallocate(allocation.shelf);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Fails without changing anything if an image no longer fits in a page, which can happen when
+    // the padding was raised after it was inserted
+    pub fn compact(
+        &mut self,
+        renderer: &Renderer,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> bool {
+        let mut ids = self.allocations.keys().copied().collect::<Vec<_>>();
+        ids.sort_unstable_by_key(|id| {
+            let allocation = &self.allocations[id];
+            (
+                std::cmp::Reverse(allocation.height),
+                std::cmp::Reverse(allocation.width),
+            )
+        });
+
+        let mut new_allocators: Vec<PageAllocator> = Vec::new();
+        let mut new_allocations = HashMap::with_capacity(ids.len());
+        for id in ids {
+            let allocation = self.allocations[&id];
+            let padded_width = allocation.width + self.padding;
+            let padded_height = allocation.height + self.padding;
+            let Some((page, shelf, x, y)) = new_allocators
+                .iter_mut()
+                .enumerate()
+                .find_map(|(i, allocator)| {
+                    allocator
+                        .allocate(padded_width, padded_height)
+                        .map(|(shelf, x, y)| (i, shelf, x, y))
+                })
+                .or_else(|| {
+                    let mut allocator = PageAllocator::new(self.page_size);
+                    let (shelf, x, y) = allocator.allocate(padded_width, padded_height)?;
+                    new_allocators.push(allocator);
+                    Some((new_allocators.len() - 1, shelf, x, y))
+                })
+            else {
+                return false;
+            };
+            new_allocations.insert(
+                id,
+                Allocation {
+                    page,
+                    shelf,
+                    x,
+                    y,
+                    ..allocation
+                },
+            );
+        }
+
+        let new_textures = new_allocators
+            .iter()
+            .map(|_| self.create_page_texture(renderer))
+            .collect::<Vec<_>>();
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: self.label.as_deref(),
+        });
+        for (id, new_allocation) in &new_allocations {
+            let old_allocation = &self.allocations[id];
+            let old_texture = renderer.texture(self.pages[old_allocation.page].texture_id);
+            let new_texture = &new_textures[new_allocation.page];
+            encoder.copy_texture_to_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &old_texture.unwrap_owned_ref().texture(device),
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: old_allocation.x,
+                        y: old_allocation.y,
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::ImageCopyTexture {
+                    texture: &new_texture.unwrap_owned_ref().texture(device),
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: new_allocation.x,
+                        y: new_allocation.y,
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::Extent3d {
+                    width: new_allocation.width,
+                    height: new_allocation.height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+        queue.submit([encoder.finish()]);
+
+        let old_pages = std::mem::take(&mut self.pages);
+        let mut old_texture_ids = old_pages.into_iter().map(|page| page.texture_id);
+        for (allocator, texture) in new_allocators.into_iter().zip(new_textures) {
+            let texture_id = match old_texture_ids.next() {
+                Some(texture_id) => {
+                    *renderer.texture_mut(texture_id) = texture;
+                    texture_id
+                }
+                None => renderer.add_texture(texture),
+            };
+            self.pages.push(Page {
+                texture_id,
+                allocator,
+            });
+        }
+        for texture_id in old_texture_ids {
+            renderer.remove_texture(texture_id);
+        }
+        self.allocations = new_allocations;
+        true
+    }
+
+    pub fn remove_empty_pages(&mut self, renderer: &Renderer) {
+        if !self.pages.iter().any(|page| page.allocator.is_empty()) {
+            return;
+        }
+        let mut page_indices = Vec::with_capacity(self.pages.len());
+        let mut new_index = 0;
+        self.pages.retain(|page| {
+            if page.allocator.is_empty() {
+                renderer.remove_texture(page.texture_id);
+                page_indices.push(usize::MAX);
+                false
+            } else {
+                page_indices.push(new_index);
+                new_index += 1;
+                true
+            }
+        });
+        for allocation in self.allocations.values_mut() {
+            allocation.page = page_indices[allocation.page];
+        }
+    }
+
+    pub fn clear(&mut self, renderer: &Renderer) {
+        for page in self.pages.drain(..) {
+            renderer.remove_texture(page.texture_id);
+        }
+        self.allocations.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocate_picks_the_tightest_shelf() {
+        let mut allocator = PageAllocator::new(64);
+        assert_eq!(allocator.allocate(10, 8), Some((0, 0, 0)));
+        assert_eq!(allocator.allocate(10, 16), Some((1, 0, 8)));
+        assert_eq!(allocator.allocate(10, 12), Some((1, 10, 8)));
+        assert_eq!(allocator.allocate(10, 7), Some((0, 10, 0)));
+        assert_eq!(allocator.allocate(60, 4), Some((2, 0, 24)));
+        // The shortest shelf is full, so the next tightest one is used
+        assert_eq!(allocator.allocate(10, 4), Some((0, 20, 0)));
+    }
+
+    #[test]
+    fn allocate_opens_shelves_until_the_page_is_full() {
+        let mut allocator = PageAllocator::new(32);
+        assert_eq!(allocator.allocate(20, 10), Some((0, 0, 0)));
+        // Too wide for the remaining space of the first shelf
+        assert_eq!(allocator.allocate(20, 10), Some((1, 0, 10)));
+        assert_eq!(allocator.allocate(12, 12), Some((2, 0, 20)));
+        assert_eq!(allocator.allocate(12, 12), Some((2, 12, 20)));
+        assert_eq!(allocator.allocate(4, 1), Some((0, 20, 0)));
+        assert_eq!(allocator.allocate(12, 13), None);
+        assert_eq!(allocator.allocate(33, 1), None);
+    }
+
+    #[test]
+    fn deallocate_pops_trailing_empty_shelves() {
+        let mut allocator = PageAllocator::new(64);
+        allocator.allocate(10, 10);
+        allocator.allocate(10, 20);
+        allocator.allocate(10, 20);
+        allocator.allocate(10, 30);
+        assert_eq!(allocator.shelves.len(), 3);
+
+        // Empty shelves in the middle are only reset, as the shelves below them would move
+        allocator.deallocate(1);
+        allocator.deallocate(1);
+        assert_eq!(allocator.shelves.len(), 3);
+        assert_eq!(allocator.shelves[1].x, 0);
+        assert_eq!(allocator.allocate(30, 15), Some((1, 0, 10)));
+        allocator.deallocate(1);
+
+        allocator.deallocate(2);
+        assert_eq!(allocator.shelves.len(), 1);
+        allocator.deallocate(0);
+        assert!(allocator.is_empty());
+        assert_eq!(allocator.allocate(64, 64), Some((0, 0, 0)));
+    }
+}
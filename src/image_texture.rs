@@ -0,0 +1,94 @@
+use crate::{
+    mipmap::MipmapGenerator, Renderer, SamplerDescriptor, SrgbMode, Texture, TextureDescriptor,
+    TextureSetRange,
+};
+use image::DynamicImage;
+use std::borrow::Cow;
+
+pub struct ImageTextureOptions {
+    pub srgb: Option<bool>,
+    pub generate_mipmaps: bool,
+    pub sampler_desc: SamplerDescriptor,
+}
+
+impl Default for ImageTextureOptions {
+    fn default() -> Self {
+        ImageTextureOptions {
+            srgb: None,
+            generate_mipmaps: false,
+            sampler_desc: SamplerDescriptor {
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                mipmap_filter: wgpu::FilterMode::Linear,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl Renderer {
+    pub fn load_image_texture(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: Option<Cow<'static, str>>,
+        bytes: &[u8],
+        options: ImageTextureOptions,
+    ) -> Result<imgui::TextureId, image::ImageError> {
+        let image = image::load_from_memory(bytes)?;
+        let (width, height) = (image.width(), image.height());
+        let (format, data) = match image {
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => (
+                wgpu::TextureFormat::Rgba32Float,
+                image
+                    .to_rgba32f()
+                    .into_raw()
+                    .into_iter()
+                    .flat_map(f32::to_ne_bytes)
+                    .collect(),
+            ),
+            _ => (
+                if options.srgb.unwrap_or(self.srgb_mode != SrgbMode::None) {
+                    wgpu::TextureFormat::Rgba8UnormSrgb
+                } else {
+                    wgpu::TextureFormat::Rgba8Unorm
+                },
+                image.to_rgba8().into_raw(),
+            ),
+        };
+
+        let generate_mipmaps =
+            options.generate_mipmaps && MipmapGenerator::supports_format(device, format);
+        let mut usage = wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING;
+        if generate_mipmaps {
+            usage |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+        }
+        let texture = self.create_owned_texture(
+            label,
+            TextureDescriptor {
+                width,
+                height,
+                depth_or_array_layers: 1,
+                mip_level_count: if generate_mipmaps {
+                    width.max(height).ilog2() + 1
+                } else {
+                    1
+                },
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage,
+            },
+            options.sampler_desc,
+        );
+        texture.set_data(device, queue, &data, TextureSetRange::default());
+        if generate_mipmaps {
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: texture.label(),
+            });
+            self.generate_mipmaps(device, &mut encoder, &texture);
+            queue.submit([encoder.finish()]);
+        }
+
+        Ok(self.add_texture(Texture::Owned(texture)))
+    }
+}
@@ -0,0 +1,8 @@
+@group(1) @binding(0) var t_texture: texture_3d<SAMPLE_TYPE>;
+
+fn sample_texture(uv: vec2<f32>) -> vec4<f32> {
+    let size = vec3<i32>(textureDimensions(t_texture));
+    let coords = clamp(vec2<i32>(floor(uv * vec2<f32>(size.xy))), vec2<i32>(0), size.xy - 1);
+    let slice = min(i32(params.display_layer), size.z - 1);
+    return vec4<f32>(textureLoad(t_texture, vec3<i32>(coords, slice), 0));
+}
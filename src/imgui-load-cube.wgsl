@@ -0,0 +1,9 @@
+@group(1) @binding(0) var t_texture: texture_2d_array<SAMPLE_TYPE>;
+
+fn sample_texture(uv: vec2<f32>) -> vec4<f32> {
+    let texel = cube_texel(uv);
+    let size = vec2<i32>(textureDimensions(t_texture));
+    let coords = clamp(vec2<i32>(floor(texel.uv * vec2<f32>(size))), vec2<i32>(0), size - 1);
+    let color = vec4<f32>(textureLoad(t_texture, coords, min(texel.face, 5u), 0));
+    return select(color, vec4<f32>(0.0), texel.face > 5u);
+}
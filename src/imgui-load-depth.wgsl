@@ -0,0 +1,7 @@
+@group(1) @binding(0) var t_texture: texture_2d<f32>;
+
+fn sample_texture(uv: vec2<f32>) -> vec4<f32> {
+    let size = vec2<i32>(textureDimensions(t_texture));
+    let coords = clamp(vec2<i32>(floor(uv * vec2<f32>(size))), vec2<i32>(0), size - 1);
+    return depth_color(textureLoad(t_texture, coords, 0).r);
+}
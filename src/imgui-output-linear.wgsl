@@ -0,0 +1,10 @@
+fn encode_hdr(color: vec3<f32>) -> vec3<f32> {
+    return color;
+}
+
+fn output_color(vert_color: vec4<f32>, tex_color: vec4<f32>) -> vec4<f32> {
+    return vec4<f32>(
+        vert_color.rgb * pow(tex_color.rgb, vec3<f32>(1.0 / 2.2)),
+        vert_color.a * tex_color.a
+    );
+}
@@ -0,0 +1,11 @@
+fn encode_hdr(color: vec3<f32>) -> vec3<f32> {
+    return color;
+}
+
+fn output_color(vert_color: vec4<f32>, tex_color: vec4<f32>) -> vec4<f32> {
+    var alpha: f32 = vert_color.a * tex_color.a;
+    return vec4<f32>(
+        pow(vert_color.rgb * alpha, vec3<f32>(2.2)) * tex_color.rgb,
+        1.0 - pow(1.0 - alpha, 2.2)
+    );
+}
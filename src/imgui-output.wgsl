@@ -0,0 +1,7 @@
+fn encode_hdr(color: vec3<f32>) -> vec3<f32> {
+    return pow(color, vec3<f32>(1.0 / 2.2));
+}
+
+fn output_color(vert_color: vec4<f32>, tex_color: vec4<f32>) -> vec4<f32> {
+    return vert_color * tex_color;
+}
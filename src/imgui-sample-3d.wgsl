@@ -0,0 +1,8 @@
+@group(1) @binding(0) var t_texture: texture_3d<f32>;
+@group(1) @binding(1) var s_texture: sampler;
+
+fn sample_texture(uv: vec2<f32>) -> vec4<f32> {
+    let depth = f32(textureDimensions(t_texture).z);
+    let w = (f32(params.display_layer) + 0.5) / depth;
+    return textureSample(t_texture, s_texture, vec3<f32>(uv, w));
+}
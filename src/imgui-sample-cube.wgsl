@@ -0,0 +1,20 @@
+@group(1) @binding(0) var t_texture: texture_cube<f32>;
+@group(1) @binding(1) var s_texture: sampler;
+
+fn cube_direction(face: u32, uv: vec2<f32>) -> vec3<f32> {
+    let st = uv * 2.0 - 1.0;
+    switch face {
+        case 0u: { return vec3<f32>(1.0, -st.y, -st.x); }
+        case 1u: { return vec3<f32>(-1.0, -st.y, st.x); }
+        case 2u: { return vec3<f32>(st.x, 1.0, st.y); }
+        case 3u: { return vec3<f32>(st.x, -1.0, -st.y); }
+        case 4u: { return vec3<f32>(st.x, -st.y, 1.0); }
+        default: { return vec3<f32>(-st.x, -st.y, -1.0); }
+    }
+}
+
+fn sample_texture(uv: vec2<f32>) -> vec4<f32> {
+    let texel = cube_texel(uv);
+    let color = textureSample(t_texture, s_texture, cube_direction(min(texel.face, 5u), texel.uv));
+    return select(color, vec4<f32>(0.0), texel.face > 5u);
+}
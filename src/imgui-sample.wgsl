@@ -0,0 +1,6 @@
+@group(1) @binding(0) var t_texture: texture_2d<f32>;
+@group(1) @binding(1) var s_texture: sampler;
+
+fn sample_texture(uv: vec2<f32>) -> vec4<f32> {
+    return textureSample(t_texture, s_texture, uv);
+}
@@ -24,13 +24,89 @@ fn vs_main(
     return output;
 }
 
-@group(1) @binding(0) var t_texture: texture_2d<f32>;
-@group(1) @binding(1) var s_texture: sampler;
+struct TextureParams {
+    swizzle: vec4<u32>,
+    depth_range: vec2<f32>,
+    linearize_depth: u32,
+    display_mode: u32,
+    display_layer: u32,
+    tone_mapping: u32,
+    exposure: f32,
+    color_matrix: mat4x4<f32>,
+    color_offset: vec4<f32>,
+}
+
+@group(1) @binding(2) var<uniform> params: TextureParams;
+
+fn swizzle_channel(color: vec4<f32>, channel: u32) -> f32 {
+    var sources = array<f32, 6>(color.r, color.g, color.b, color.a, 0.0, 1.0);
+    return sources[min(channel, 5u)];
+}
+
+fn swizzle(color: vec4<f32>) -> vec4<f32> {
+    return vec4<f32>(
+        swizzle_channel(color, params.swizzle.x),
+        swizzle_channel(color, params.swizzle.y),
+        swizzle_channel(color, params.swizzle.z),
+        swizzle_channel(color, params.swizzle.w),
+    );
+}
+
+fn tone_map(color: vec3<f32>) -> vec3<f32> {
+    let positive = max(color, vec3<f32>(0.0));
+    switch params.tone_mapping {
+        case 1u: {
+            return encode_hdr(min(positive, vec3<f32>(1.0)));
+        }
+        case 2u: {
+            return encode_hdr(positive / (1.0 + positive));
+        }
+        case 3u: {
+            // Narkowicz's fit of the ACES filmic curve
+            let mapped = (positive * (2.51 * positive + 0.03))
+                / (positive * (2.43 * positive + 0.59) + 0.14);
+            return encode_hdr(min(mapped, vec3<f32>(1.0)));
+        }
+        default: {
+            return color;
+        }
+    }
+}
+
+struct CubeTexel {
+    face: u32,
+    uv: vec2<f32>,
+}
+
+// Face 6 marks the empty cells of the cross layout
+fn cube_texel(uv: vec2<f32>) -> CubeTexel {
+    if params.display_mode != 2u {
+        return CubeTexel(min(params.display_layer, 5u), uv);
+    }
+    // Horizontal cross, with +Y above and -Y below the -X +Z +X -Z row
+    var faces = array<u32, 12>(6u, 2u, 6u, 6u, 1u, 4u, 0u, 5u, 6u, 3u, 6u, 6u);
+    let cross_uv = uv * vec2<f32>(4.0, 3.0);
+    let cell = vec2<u32>(clamp(cross_uv, vec2<f32>(0.0), vec2<f32>(3.0, 2.0)));
+    return CubeTexel(faces[cell.y * 4u + cell.x], fract(cross_uv));
+}
+
+fn depth_color(raw_depth: f32) -> vec4<f32> {
+    let near = params.depth_range.x;
+    let far = params.depth_range.y;
+    var depth = raw_depth;
+    if params.linearize_depth != 0u {
+        depth = near * far / (far - depth * (far - near));
+    }
+    let value = clamp((depth - near) / (far - near), 0.0, 1.0);
+    return vec4<f32>(vec3<f32>(value), 1.0);
+}
 
 @fragment
 fn fs_main(
     @location(0) uv: vec2<f32>,
     @location(1) vert_color: vec4<f32>,
 ) -> @location(0) vec4<f32> {
-    return vert_color * textureSample(t_texture, s_texture, uv);
+    let color = params.color_matrix * swizzle(sample_texture(uv)) + params.color_offset;
+    let exposed = color.rgb * exp2(params.exposure);
+    return output_color(vert_color, vec4<f32>(tone_map(exposed), color.a));
 }
@@ -1,17 +1,39 @@
-use ahash::AHashMap as HashMap;
+mod atlas;
+#[cfg(feature = "image")]
+mod image_texture;
+mod mipmap;
+mod registry;
+mod shadow;
+mod staging_belt;
+
+use ahash::{AHashMap as HashMap, AHashSet as HashSet};
+pub use atlas::{AtlasImage, AtlasImageId, TextureAtlas};
+#[cfg(feature = "image")]
+pub use image_texture::ImageTextureOptions;
 use imgui::internal::RawWrapper;
+use mipmap::MipmapGenerator;
+use registry::{Lookup, SharedRegistry, TextureRegistry, FONT_TEXTURE_ID};
+pub use registry::{TextureError, TextureRegistryHandle};
+use shadow::{DirtyRect, ShadowCopy};
+pub use staging_belt::TextureStagingBelt;
 use std::{
     borrow::Cow,
-    cell::{Cell, Ref, RefCell, RefMut},
+    cell::{Cell, OnceCell, Ref, RefCell, RefMut},
+    error::Error,
+    fmt,
+    hash::{Hash, Hasher},
     mem::{replace, size_of, size_of_val},
     num::NonZeroU64,
     slice,
+    sync::{Arc, Weak},
 };
 
 pub struct TextureDescriptor {
     pub width: u32,
     pub height: u32,
+    pub depth_or_array_layers: u32,
     pub mip_level_count: u32,
+    pub dimension: wgpu::TextureDimension,
     pub format: wgpu::TextureFormat,
     pub usage: wgpu::TextureUsages,
 }
@@ -23,21 +45,70 @@ impl TextureDescriptor {
             size: wgpu::Extent3d {
                 width: self.width,
                 height: self.height,
-                depth_or_array_layers: 1,
+                depth_or_array_layers: self.depth_or_array_layers,
             },
             mip_level_count: self.mip_level_count,
             sample_count: 1,
-            dimension: wgpu::TextureDimension::D2,
+            dimension: self.dimension,
             format: self.format,
             usage: self.usage,
             view_formats: &[],
         }
     }
 
-    fn bytes_per_row(&self) -> Option<u32> {
-        let block_dimensions = self.format.block_dimensions();
+    fn mip_level_size(&self, mip_level: u32) -> (u32, u32) {
+        let (block_width, block_height) = self.format.block_dimensions();
+        (
+            (self.width >> mip_level)
+                .max(1)
+                .next_multiple_of(block_width),
+            (self.height >> mip_level)
+                .max(1)
+                .next_multiple_of(block_height),
+        )
+    }
+
+    fn layer_count(&self, mip_level: u32) -> u32 {
+        if self.dimension == wgpu::TextureDimension::D3 {
+            (self.depth_or_array_layers >> mip_level).max(1)
+        } else {
+            self.depth_or_array_layers
+        }
+    }
+
+    fn bytes_per_row(&self, mip_level: u32) -> Option<u32> {
+        let block_width = self.format.block_dimensions().0;
         let block_size = self.format.block_copy_size(None)?;
-        Some(self.width / block_dimensions.0 * block_size)
+        Some(self.mip_level_size(mip_level).0 / block_width * block_size)
+    }
+
+    fn supports_shadow_copy(&self) -> bool {
+        self.format.block_dimensions() == (1, 1) && self.format.block_copy_size(None).is_some()
+    }
+
+    // Formats whose size is up to the backend, like `Depth24Plus`, and multi-planar formats can't
+    // be sized
+    pub fn memory_size(&self) -> Option<u64> {
+        let (block_width, block_height) = self.format.block_dimensions();
+        // Combined depth-stencil formats have no single copy size, so add up their aspects
+        let aspect_size = |aspect| self.format.block_copy_size(Some(aspect));
+        let block_size = match self.format.block_copy_size(None) {
+            Some(block_size) => block_size,
+            None => {
+                aspect_size(wgpu::TextureAspect::DepthOnly)?
+                    + aspect_size(wgpu::TextureAspect::StencilOnly)?
+            }
+        };
+        let memory_size = (0..self.mip_level_count)
+            .map(|mip_level| {
+                let (width, height) = self.mip_level_size(mip_level);
+                (width / block_width) as u64
+                    * (height / block_height) as u64
+                    * self.layer_count(mip_level) as u64
+                    * block_size as u64
+            })
+            .sum();
+        Some(memory_size)
     }
 }
 
@@ -46,13 +117,16 @@ impl Default for TextureDescriptor {
         TextureDescriptor {
             width: 1,
             height: 1,
+            depth_or_array_layers: 1,
             mip_level_count: 1,
+            dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Rgba8Unorm,
             usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
         }
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct SamplerDescriptor {
     pub address_mode_u: wgpu::AddressMode,
     pub address_mode_v: wgpu::AddressMode,
@@ -83,6 +157,36 @@ impl SamplerDescriptor {
     }
 }
 
+impl PartialEq for SamplerDescriptor {
+    fn eq(&self, other: &Self) -> bool {
+        self.address_mode_u == other.address_mode_u
+            && self.address_mode_v == other.address_mode_v
+            && self.mag_filter == other.mag_filter
+            && self.min_filter == other.min_filter
+            && self.mipmap_filter == other.mipmap_filter
+            && self.lod_min_clamp.to_bits() == other.lod_min_clamp.to_bits()
+            && self.lod_max_clamp.to_bits() == other.lod_max_clamp.to_bits()
+            && self.anisotropy_clamp == other.anisotropy_clamp
+            && self.border_color == other.border_color
+    }
+}
+
+impl Eq for SamplerDescriptor {}
+
+impl Hash for SamplerDescriptor {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.address_mode_u.hash(state);
+        self.address_mode_v.hash(state);
+        self.mag_filter.hash(state);
+        self.min_filter.hash(state);
+        self.mipmap_filter.hash(state);
+        self.lod_min_clamp.to_bits().hash(state);
+        self.lod_max_clamp.to_bits().hash(state);
+        self.anisotropy_clamp.hash(state);
+        self.border_color.hash(state);
+    }
+}
+
 impl Default for SamplerDescriptor {
     fn default() -> Self {
         SamplerDescriptor {
@@ -92,7 +196,7 @@ impl Default for SamplerDescriptor {
             min_filter: Default::default(),
             mipmap_filter: Default::default(),
             lod_min_clamp: 0.0,
-            lod_max_clamp: std::f32::MAX,
+            lod_max_clamp: f32::MAX,
             anisotropy_clamp: 1,
             border_color: None,
         }
@@ -102,6 +206,7 @@ impl Default for SamplerDescriptor {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub struct TextureSetRange {
     pub mip_level: u32,
+    pub layer: u32,
     pub x: u32,
     pub y: u32,
     pub width: Option<u32>,
@@ -109,13 +214,687 @@ pub struct TextureSetRange {
     pub offset: u64,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureDataError {
+    UnsupportedFormat(wgpu::TextureFormat),
+    MipLevelOutOfRange {
+        mip_level: u32,
+        mip_level_count: u32,
+    },
+    LayerOutOfRange {
+        layer: u32,
+        layer_count: u32,
+    },
+    UnalignedRange {
+        format: wgpu::TextureFormat,
+        block_width: u32,
+        block_height: u32,
+    },
+    RangeOutOfBounds {
+        mip_level: u32,
+        width: u32,
+        height: u32,
+    },
+    UnalignedOffset {
+        offset: u64,
+        alignment: u32,
+    },
+    NoShadowCopy,
+    ShadowCopyMipLevel(u32),
+    ShadowCopyNotUpdatable,
+    DataTooShort {
+        required: u64,
+        len: usize,
+    },
+    MissingUsage(wgpu::TextureUsages),
+    MissingBufferUsage(wgpu::BufferUsages),
+    UnalignedBytesPerRow(u32),
+    BytesPerRowTooSmall {
+        bytes_per_row: u32,
+        row_size: u32,
+    },
+}
+
+impl fmt::Display for TextureDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureDataError::UnsupportedFormat(format) => {
+                write!(f, "copying data is not supported for {format:?} textures")
+            }
+            TextureDataError::MipLevelOutOfRange {
+                mip_level,
+                mip_level_count,
+            } => write!(
+                f,
+                "mip level {mip_level} is out of range for a texture with {mip_level_count} mip \
+                 levels"
+            ),
+            TextureDataError::LayerOutOfRange { layer, layer_count } => write!(
+                f,
+                "layer {layer} is out of range for a texture with {layer_count} layers"
+            ),
+            TextureDataError::UnalignedRange {
+                format,
+                block_width,
+                block_height,
+            } => write!(
+                f,
+                "texture range must be aligned to the {block_width}x{block_height} blocks of \
+                 {format:?} textures"
+            ),
+            TextureDataError::RangeOutOfBounds {
+                mip_level,
+                width,
+                height,
+            } => write!(
+                f,
+                "texture range exceeds the {width}x{height} size of mip level {mip_level}"
+            ),
+            TextureDataError::UnalignedOffset { offset, alignment } => write!(
+                f,
+                "data offset {offset} is not a multiple of {alignment} bytes"
+            ),
+            TextureDataError::NoShadowCopy => write!(f, "texture has no shadow copy"),
+            TextureDataError::ShadowCopyMipLevel(mip_level) => write!(
+                f,
+                "shadow copies only cover mip level 0, not mip level {mip_level}"
+            ),
+            TextureDataError::ShadowCopyNotUpdatable => write!(
+                f,
+                "copies from GPU buffers can't update the shadow copy of mip level 0"
+            ),
+            TextureDataError::DataTooShort { required, len } => write!(
+                f,
+                "texture data is {len} bytes long but the range requires {required} bytes"
+            ),
+            TextureDataError::MissingUsage(usage) => {
+                write!(f, "texture needs {usage:?} usage for this operation")
+            }
+            TextureDataError::MissingBufferUsage(usage) => {
+                write!(f, "buffer needs {usage:?} usage for this operation")
+            }
+            TextureDataError::UnalignedBytesPerRow(bytes_per_row) => write!(
+                f,
+                "buffer row pitch {bytes_per_row} is not a multiple of {} bytes",
+                wgpu::COPY_BYTES_PER_ROW_ALIGNMENT
+            ),
+            TextureDataError::BytesPerRowTooSmall {
+                bytes_per_row,
+                row_size,
+            } => write!(
+                f,
+                "buffer row pitch {bytes_per_row} is smaller than the {row_size} bytes of each row"
+            ),
+        }
+    }
+}
+
+impl Error for TextureDataError {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DepthDisplay {
+    pub near: f32,
+    pub far: f32,
+    pub linearize: bool,
+}
+
+impl Default for DepthDisplay {
+    fn default() -> Self {
+        DepthDisplay {
+            near: 0.0,
+            far: 1.0,
+            linearize: false,
+        }
+    }
+}
+
+impl DepthDisplay {
+    // Linearizing assumes a perspective projection, which maps a near plane at 0 to black.
+    // Reversed-Z depth is linearized by swapping the planes, so that `near` is the far plane's
+    // distance
+    fn validate(&self) -> Result<(), DepthDisplayError> {
+        if self.near == self.far {
+            return Err(DepthDisplayError::EmptyRange(self.near));
+        }
+        if self.linearize && !(self.near > 0.0 && self.far > 0.0) {
+            return Err(DepthDisplayError::InvalidLinearRange {
+                near: self.near,
+                far: self.far,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DepthDisplayError {
+    EmptyRange(f32),
+    InvalidLinearRange { near: f32, far: f32 },
+}
+
+impl fmt::Display for DepthDisplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DepthDisplayError::EmptyRange(depth) => {
+                write!(f, "depth range is empty, as near and far are both {depth}")
+            }
+            DepthDisplayError::InvalidLinearRange { near, far } => write!(
+                f,
+                "linearized depth needs positive near and far planes, but near is {near} and far \
+                 is {far}"
+            ),
+        }
+    }
+}
+
+impl Error for DepthDisplayError {}
+
+// Applied after the color transform and the per-texture exposure
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ToneMapping {
+    #[default]
+    None,
+    Clamp,
+    Reinhard,
+    Aces,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Channel {
+    R,
+    G,
+    B,
+    A,
+    Zero,
+    One,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Swizzle {
+    pub r: Channel,
+    pub g: Channel,
+    pub b: Channel,
+    pub a: Channel,
+}
+
+impl Swizzle {
+    pub const IDENTITY: Self = Swizzle {
+        r: Channel::R,
+        g: Channel::G,
+        b: Channel::B,
+        a: Channel::A,
+    };
+
+    pub const GRAYSCALE: Self = Swizzle::isolate(Channel::R);
+
+    pub const ALPHA_MASK: Self = Swizzle {
+        r: Channel::One,
+        g: Channel::One,
+        b: Channel::One,
+        a: Channel::R,
+    };
+
+    pub const fn isolate(channel: Channel) -> Self {
+        Swizzle {
+            r: channel,
+            g: channel,
+            b: channel,
+            a: Channel::One,
+        }
+    }
+}
+
+impl Default for Swizzle {
+    fn default() -> Self {
+        Swizzle::IDENTITY
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorTransform {
+    pub matrix: [[f32; 4]; 4],
+    pub offset: [f32; 4],
+}
+
+impl ColorTransform {
+    pub const IDENTITY: Self = ColorTransform::scale([1.0; 4]);
+
+    pub const INVERT: Self = ColorTransform {
+        matrix: [
+            [-1.0, 0.0, 0.0, 0.0],
+            [0.0, -1.0, 0.0, 0.0],
+            [0.0, 0.0, -1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+        offset: [1.0, 1.0, 1.0, 0.0],
+    };
+
+    pub const fn scale(factors: [f32; 4]) -> Self {
+        ColorTransform {
+            matrix: [
+                [factors[0], 0.0, 0.0, 0.0],
+                [0.0, factors[1], 0.0, 0.0],
+                [0.0, 0.0, factors[2], 0.0],
+                [0.0, 0.0, 0.0, factors[3]],
+            ],
+            offset: [0.0; 4],
+        }
+    }
+
+    pub fn exposure(stops: f32) -> Self {
+        let factor = stops.exp2();
+        ColorTransform::scale([factor, factor, factor, 1.0])
+    }
+
+    pub fn tint(color: [f32; 4]) -> Self {
+        ColorTransform::scale(color)
+    }
+
+    pub fn then(self, next: ColorTransform) -> Self {
+        let mut result = ColorTransform {
+            matrix: [[0.0; 4]; 4],
+            offset: next.offset,
+        };
+        for row in 0..4 {
+            for column in 0..4 {
+                result.matrix[row][column] = (0..4)
+                    .map(|i| next.matrix[row][i] * self.matrix[i][column])
+                    .sum();
+            }
+            result.offset[row] += (0..4)
+                .map(|i| next.matrix[row][i] * self.offset[i])
+                .sum::<f32>();
+        }
+        result
+    }
+
+    fn to_raw(self) -> [u32; 20] {
+        let mut raw = [0; 20];
+        for column in 0..4 {
+            for row in 0..4 {
+                raw[column * 4 + row] = self.matrix[row][column].to_bits();
+            }
+        }
+        for (i, offset) in self.offset.iter().enumerate() {
+            raw[16 + i] = offset.to_bits();
+        }
+        raw
+    }
+}
+
+impl Default for ColorTransform {
+    fn default() -> Self {
+        ColorTransform::IDENTITY
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TextureViewRange {
+    pub base_mip_level: u32,
+    pub mip_level_count: Option<u32>,
+    pub base_array_layer: u32,
+    pub aspect: wgpu::TextureAspect,
+}
+
+impl TextureViewRange {
+    fn resolve_aspect(&self, format: wgpu::TextureFormat) -> wgpu::TextureAspect {
+        if self.aspect == wgpu::TextureAspect::All && format.has_depth_aspect() {
+            wgpu::TextureAspect::DepthOnly
+        } else {
+            self.aspect
+        }
+    }
+
+    fn sample_type(
+        &self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        display: TextureDisplay,
+    ) -> wgpu::TextureSampleType {
+        let aspect = self.resolve_aspect(format);
+        let sample_type =
+            display_sample_type(format.sample_type(Some(aspect), Some(device.features())));
+        // Cube views are created without knowing the device, so formats that are only filterable
+        // with extra features are always loaded from a 2D array view of their faces
+        if display.is_cube() && self.base_sample_type(format) != FILTERABLE_FLOAT {
+            match sample_type {
+                FILTERABLE_FLOAT => wgpu::TextureSampleType::Float { filterable: false },
+                sample_type => sample_type,
+            }
+        } else {
+            sample_type
+        }
+    }
+
+    fn base_sample_type(&self, format: wgpu::TextureFormat) -> wgpu::TextureSampleType {
+        display_sample_type(format.sample_type(Some(self.resolve_aspect(format)), None))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureViewRangeError {
+    MipLevelsOutOfRange {
+        base_mip_level: u32,
+        mip_level_count: Option<u32>,
+        texture_mip_level_count: u32,
+    },
+    LayerOutOfRange {
+        layer: u32,
+        layer_count: u32,
+    },
+    MissingAspect {
+        aspect: wgpu::TextureAspect,
+        format: wgpu::TextureFormat,
+    },
+    NotLayered(wgpu::TextureDimension),
+}
+
+impl fmt::Display for TextureViewRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureViewRangeError::MipLevelsOutOfRange {
+                base_mip_level,
+                mip_level_count,
+                texture_mip_level_count,
+            } => write!(
+                f,
+                "{} mip levels from mip level {base_mip_level} are out of range for a texture \
+                 with {texture_mip_level_count} mip levels",
+                mip_level_count.unwrap_or(1)
+            ),
+            TextureViewRangeError::LayerOutOfRange { layer, layer_count } => write!(
+                f,
+                "layer {layer} is out of range for a texture with {layer_count} layers"
+            ),
+            TextureViewRangeError::MissingAspect { aspect, format } => {
+                write!(f, "{format:?} textures have no {aspect:?} aspect")
+            }
+            TextureViewRangeError::NotLayered(dimension) => {
+                write!(f, "{dimension:?} textures have no 2D layers to view")
+            }
+        }
+    }
+}
+
+impl Error for TextureViewRangeError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureViewError {
+    Range(TextureViewRangeError),
+    Display(TextureDisplayError),
+}
+
+impl From<TextureViewRangeError> for TextureViewError {
+    fn from(err: TextureViewRangeError) -> Self {
+        TextureViewError::Range(err)
+    }
+}
+
+impl From<TextureDisplayError> for TextureViewError {
+    fn from(err: TextureDisplayError) -> Self {
+        TextureViewError::Display(err)
+    }
+}
+
+impl fmt::Display for TextureViewError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureViewError::Range(err) => err.fmt(f),
+            TextureViewError::Display(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for TextureViewError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CubeFace {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TextureDisplay {
+    #[default]
+    Layer,
+    CubeFace(CubeFace),
+    CubeCross,
+    Slice(u32),
+}
+
+impl TextureDisplay {
+    pub fn view_dimension(
+        self,
+        sample_type: wgpu::TextureSampleType,
+    ) -> wgpu::TextureViewDimension {
+        match self {
+            TextureDisplay::Layer => wgpu::TextureViewDimension::D2,
+            // Cube views can only be sampled, so other sample types load the six faces from a
+            // 2D array view instead
+            TextureDisplay::CubeFace(_) | TextureDisplay::CubeCross
+                if sample_type == FILTERABLE_FLOAT =>
+            {
+                wgpu::TextureViewDimension::Cube
+            }
+            TextureDisplay::CubeFace(_) | TextureDisplay::CubeCross => {
+                wgpu::TextureViewDimension::D2Array
+            }
+            TextureDisplay::Slice(_) => wgpu::TextureViewDimension::D3,
+        }
+    }
+
+    fn is_cube(self) -> bool {
+        matches!(
+            self,
+            TextureDisplay::CubeFace(_) | TextureDisplay::CubeCross
+        )
+    }
+
+    fn to_raw(self) -> [u32; 2] {
+        match self {
+            TextureDisplay::Layer => [0, 0],
+            TextureDisplay::CubeFace(face) => [1, face as u32],
+            TextureDisplay::CubeCross => [2, 0],
+            TextureDisplay::Slice(slice) => [3, slice],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureDisplayError {
+    NotCube {
+        dimension: wgpu::TextureDimension,
+        layer_count: u32,
+    },
+    NotVolume(wgpu::TextureDimension),
+    DepthVolume,
+}
+
+impl fmt::Display for TextureDisplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureDisplayError::NotCube {
+                dimension,
+                layer_count,
+            } => write!(
+                f,
+                "cube displays need a 2D texture with at least 6 layers, not a {dimension:?} \
+                 texture with {layer_count} layers"
+            ),
+            TextureDisplayError::NotVolume(dimension) => write!(
+                f,
+                "slice displays need a 3D texture, not a {dimension:?} texture"
+            ),
+            TextureDisplayError::DepthVolume => {
+                write!(f, "depth textures can't be displayed as 3D slices")
+            }
+        }
+    }
+}
+
+impl Error for TextureDisplayError {}
+
+struct TextureParams {
+    swizzle: Swizzle,
+    depth_display: DepthDisplay,
+    display: TextureDisplay,
+    color_transform: ColorTransform,
+    tone_mapping: ToneMapping,
+    exposure: f32,
+    buffer: RefCell<Option<wgpu::Buffer>>,
+    changed: Cell<bool>,
+}
+
+impl TextureParams {
+    #[must_use]
+    fn new() -> Self {
+        TextureParams {
+            swizzle: Swizzle::default(),
+            depth_display: DepthDisplay::default(),
+            display: TextureDisplay::default(),
+            color_transform: ColorTransform::default(),
+            tone_mapping: ToneMapping::default(),
+            exposure: 0.0,
+            buffer: RefCell::new(None),
+            changed: Cell::new(false),
+        }
+    }
+
+    fn mark_changed(&mut self) {
+        *self.changed.get_mut() = true;
+    }
+
+    fn to_raw(&self, display: TextureDisplay) -> [u32; 32] {
+        let [display_mode, display_layer] = display.to_raw();
+        let mut raw = [0; 32];
+        raw[..11].copy_from_slice(&[
+            self.swizzle.r as u32,
+            self.swizzle.g as u32,
+            self.swizzle.b as u32,
+            self.swizzle.a as u32,
+            self.depth_display.near.to_bits(),
+            self.depth_display.far.to_bits(),
+            self.depth_display.linearize as u32,
+            display_mode,
+            display_layer,
+            self.tone_mapping as u32,
+            self.exposure.to_bits(),
+        ]);
+        raw[12..].copy_from_slice(&self.color_transform.to_raw());
+        raw
+    }
+
+    fn create_buffer(
+        &self,
+        device: &wgpu::Device,
+        label: Option<&str>,
+        display: TextureDisplay,
+    ) -> wgpu::Buffer {
+        let raw = self.to_raw(display);
+        self.changed.set(false);
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label,
+            size: size_of_val(&raw) as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: true,
+        });
+        buffer
+            .slice(..)
+            .get_mapped_range_mut()
+            .copy_from_slice(unsafe {
+                slice::from_raw_parts(raw.as_ptr() as *const u8, size_of_val(&raw))
+            });
+        buffer.unmap();
+        buffer
+    }
+
+    // Changes are written into the existing buffer, so that bind groups can keep using it
+    fn flush(&self, queue: &wgpu::Queue, display: TextureDisplay) {
+        if !self.changed.replace(false) {
+            return;
+        }
+        if let Some(buffer) = self.buffer.borrow().as_ref() {
+            let raw = self.to_raw(display);
+            queue.write_buffer(buffer, 0, unsafe {
+                slice::from_raw_parts(raw.as_ptr() as *const u8, size_of_val(&raw))
+            });
+        }
+    }
+}
+
+const FILTERABLE_FLOAT: wgpu::TextureSampleType =
+    wgpu::TextureSampleType::Float { filterable: true };
+
+fn display_sample_type(sample_type: Option<wgpu::TextureSampleType>) -> wgpu::TextureSampleType {
+    sample_type.unwrap_or(FILTERABLE_FLOAT)
+}
+
+// Buffer copies of more than one row need an aligned pitch, and wgpu checks it even for a single
+// row if one is given
+fn buffer_copy_bytes_per_row(
+    bytes_per_row: u32,
+    row_size: u32,
+    rows: u32,
+) -> Result<Option<u32>, TextureDataError> {
+    if rows <= 1 {
+        return Ok(None);
+    }
+    if bytes_per_row < row_size {
+        return Err(TextureDataError::BytesPerRowTooSmall {
+            bytes_per_row,
+            row_size,
+        });
+    }
+    if !bytes_per_row.is_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) {
+        return Err(TextureDataError::UnalignedBytesPerRow(bytes_per_row));
+    }
+    Ok(Some(bytes_per_row))
+}
+
+fn create_texture_bind_group(
+    device: &wgpu::Device,
+    label: Option<&str>,
+    bind_group_layout: &wgpu::BindGroupLayout,
+    texture_view: &wgpu::TextureView,
+    sampler: Option<&wgpu::Sampler>,
+    params_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    let mut entries = vec![
+        wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(texture_view),
+        },
+        wgpu::BindGroupEntry {
+            binding: 2,
+            resource: params_buffer.as_entire_binding(),
+        },
+    ];
+    if let Some(sampler) = sampler {
+        entries.push(wgpu::BindGroupEntry {
+            binding: 1,
+            resource: wgpu::BindingResource::Sampler(sampler),
+        });
+    }
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label,
+        layout: bind_group_layout,
+        entries: &entries,
+    })
+}
+
 pub struct OwnedTexture {
     label: Option<Cow<'static, str>>,
     texture_desc: TextureDescriptor,
-    texture_bytes_per_row: Option<u32>,
     texture_data: RefCell<Option<(wgpu::Texture, wgpu::TextureView)>>,
+    texture_generation: Cell<u64>,
+    shadow: RefCell<Option<ShadowCopy>>,
+    view_range: TextureViewRange,
     sampler_desc: SamplerDescriptor,
-    sampler: RefCell<Option<wgpu::Sampler>>,
+    params: TextureParams,
     bind_group: RefCell<Option<wgpu::BindGroup>>,
 }
 
@@ -124,7 +903,15 @@ macro_rules! owned_texture_texture_data {
         $texture_data.get_or_insert_with(|| {
             let raw_desc = $self.texture_desc.to_raw($self.label.as_deref());
             let texture = $device.create_texture(&raw_desc);
-            let view = texture.create_view(&Default::default());
+            $self
+                .texture_generation
+                .set($self.texture_generation.get() + 1);
+            let view = OwnedTexture::create_display_view_raw(
+                &texture,
+                $self.label.as_deref(),
+                &$self.view_range,
+                $self.display(),
+            );
             (texture, view)
         })
     };
@@ -139,11 +926,13 @@ impl OwnedTexture {
     ) -> Self {
         OwnedTexture {
             label,
-            texture_bytes_per_row: texture_desc.bytes_per_row(),
             texture_desc,
             texture_data: RefCell::new(None),
+            texture_generation: Cell::new(0),
+            shadow: RefCell::new(None),
+            view_range: TextureViewRange::default(),
             sampler_desc,
-            sampler: RefCell::new(None),
+            params: TextureParams::new(),
             bind_group: RefCell::new(None),
         }
     }
@@ -155,7 +944,7 @@ impl OwnedTexture {
     pub fn set_label(&mut self, value: Option<Cow<'static, str>>) {
         self.label = value;
         *self.texture_data.get_mut() = None;
-        *self.sampler.get_mut() = None;
+        *self.params.buffer.get_mut() = None;
         *self.bind_group.get_mut() = None;
     }
 
@@ -165,91 +954,989 @@ impl OwnedTexture {
 
     pub fn set_texture_desc(&mut self, value: TextureDescriptor) {
         self.texture_desc = value;
+        if self.validate_display(self.params.display).is_err() {
+            self.params.display = TextureDisplay::Layer;
+        }
+        if self.validate_view_range(&self.view_range).is_err() {
+            self.view_range = TextureViewRange::default();
+        }
         *self.texture_data.get_mut() = None;
+        self.params.mark_changed();
         *self.bind_group.get_mut() = None;
+        // The shadow copy is dropped if the new format can't have one
+        if self.shadow.get_mut().is_some() {
+            *self.shadow.get_mut() = self
+                .texture_desc
+                .supports_shadow_copy()
+                .then(|| self.create_shadow_copy());
+        }
     }
 
-    pub fn texture_bytes_per_row(&mut self) -> Option<u32> {
-        self.texture_bytes_per_row
+    pub fn texture_bytes_per_row(&self) -> Option<u32> {
+        self.texture_desc.bytes_per_row(0)
     }
 
-    pub fn sampler_desc(&self) -> &SamplerDescriptor {
-        &self.sampler_desc
+    pub fn is_created(&self) -> bool {
+        self.texture_data.borrow().is_some()
     }
 
-    pub fn set_sampler_desc(&mut self, value: SamplerDescriptor) {
-        self.sampler_desc = value;
-        *self.sampler.get_mut() = None;
-        *self.bind_group.get_mut() = None;
+    pub fn memory_size(&self) -> Option<u64> {
+        self.texture_desc.memory_size()
     }
 
-    fn update_bind_group(&self, device: &wgpu::Device, bind_group_layout: &wgpu::BindGroupLayout) {
-        let mut texture_data = self.texture_data.borrow_mut();
-        let texture_view = &owned_texture_texture_data!(texture_data, self, device).1;
-        let mut sampler = self.sampler.borrow_mut();
-        let sampler = sampler.get_or_insert_with(|| {
-            device.create_sampler(&self.sampler_desc.to_raw(self.label.as_deref()))
+    pub fn texture(&self, device: &wgpu::Device) -> Ref<'_, wgpu::Texture> {
+        owned_texture_texture_data!(self.texture_data.borrow_mut(), self, device);
+        Ref::map(self.texture_data.borrow(), |texture_data| {
+            &texture_data.as_ref().unwrap().0
+        })
+    }
+
+    // The view imgui displays, which only covers the depth aspect of depth-stencil formats and the
+    // selected mip levels. Render passes should use `create_attachment_view` instead
+    pub fn view(&self, device: &wgpu::Device) -> Ref<'_, wgpu::TextureView> {
+        owned_texture_texture_data!(self.texture_data.borrow_mut(), self, device);
+        Ref::map(self.texture_data.borrow(), |texture_data| {
+            &texture_data.as_ref().unwrap().1
+        })
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let (width, height) = (width.max(1), height.max(1));
+        if width == self.texture_desc.width && height == self.texture_desc.height {
+            return;
+        }
+        self.set_texture_desc(TextureDescriptor {
+            width,
+            height,
+            ..self.texture_desc
         });
-        let mut bind_group = self.bind_group.borrow_mut();
-        if bind_group.is_none() {
-            *bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: self.label.as_deref(),
-                layout: bind_group_layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureView(texture_view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::Sampler(sampler),
-                    },
-                ],
-            }));
+    }
+
+    pub fn layer(&self) -> u32 {
+        self.view_range.base_array_layer
+    }
+
+    #[track_caller]
+    pub fn set_layer(&mut self, value: u32) {
+        self.try_set_layer(value)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    pub fn try_set_layer(&mut self, value: u32) -> Result<(), TextureViewRangeError> {
+        self.try_set_view_range(TextureViewRange {
+            base_array_layer: value,
+            ..self.view_range
+        })
+    }
+
+    pub fn view_range(&self) -> &TextureViewRange {
+        &self.view_range
+    }
+
+    #[track_caller]
+    pub fn set_view_range(&mut self, value: TextureViewRange) {
+        self.try_set_view_range(value)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    pub fn try_set_view_range(
+        &mut self,
+        value: TextureViewRange,
+    ) -> Result<(), TextureViewRangeError> {
+        self.validate_view_range(&value)?;
+        self.view_range = value;
+        self.params.mark_changed();
+        self.recreate_view();
+        Ok(())
+    }
+
+    pub fn display(&self) -> TextureDisplay {
+        self.resolve_display(&self.view_range, self.params.display)
+    }
+
+    // 3D textures can't be viewed as a single 2D layer, so show the matching slice instead
+    fn resolve_display(&self, range: &TextureViewRange, display: TextureDisplay) -> TextureDisplay {
+        match display {
+            TextureDisplay::Layer if self.texture_desc.dimension == wgpu::TextureDimension::D3 => {
+                TextureDisplay::Slice(range.base_array_layer)
+            }
+            display => display,
+        }
+    }
+
+    #[track_caller]
+    pub fn set_display(&mut self, value: TextureDisplay) {
+        self.try_set_display(value)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    pub fn try_set_display(&mut self, value: TextureDisplay) -> Result<(), TextureDisplayError> {
+        self.validate_display(value)?;
+        self.params.display = value;
+        self.params.mark_changed();
+        self.recreate_view();
+        Ok(())
+    }
+
+    fn validate_display(&self, display: TextureDisplay) -> Result<(), TextureDisplayError> {
+        let desc = &self.texture_desc;
+        match display {
+            TextureDisplay::Layer => Ok(()),
+            TextureDisplay::CubeFace(_) | TextureDisplay::CubeCross => {
+                if desc.dimension == wgpu::TextureDimension::D2 && desc.depth_or_array_layers >= 6 {
+                    Ok(())
+                } else {
+                    Err(TextureDisplayError::NotCube {
+                        dimension: desc.dimension,
+                        layer_count: desc.depth_or_array_layers,
+                    })
+                }
+            }
+            TextureDisplay::Slice(_) if desc.dimension != wgpu::TextureDimension::D3 => {
+                Err(TextureDisplayError::NotVolume(desc.dimension))
+            }
+            TextureDisplay::Slice(_) if desc.format.has_depth_aspect() => {
+                Err(TextureDisplayError::DepthVolume)
+            }
+            TextureDisplay::Slice(_) => Ok(()),
+        }
+    }
+
+    fn validate_view_range(&self, range: &TextureViewRange) -> Result<(), TextureViewRangeError> {
+        let desc = &self.texture_desc;
+        let aspect_exists = match range.aspect {
+            wgpu::TextureAspect::All => true,
+            wgpu::TextureAspect::DepthOnly => desc.format.has_depth_aspect(),
+            wgpu::TextureAspect::StencilOnly => desc.format.has_stencil_aspect(),
+            _ => false,
+        };
+        if !aspect_exists {
+            return Err(TextureViewRangeError::MissingAspect {
+                aspect: range.aspect,
+                format: desc.format,
+            });
+        }
+        let mip_level_count = range.mip_level_count.unwrap_or(1);
+        if mip_level_count == 0
+            || range.base_mip_level as u64 + mip_level_count as u64 > desc.mip_level_count as u64
+        {
+            return Err(TextureViewRangeError::MipLevelsOutOfRange {
+                base_mip_level: range.base_mip_level,
+                mip_level_count: range.mip_level_count,
+                texture_mip_level_count: desc.mip_level_count,
+            });
+        }
+        // The layer of a 3D texture picks the slice to display
+        let layer_count = match desc.dimension {
+            wgpu::TextureDimension::D1 => 1,
+            _ => desc.depth_or_array_layers,
+        };
+        if range.base_array_layer >= layer_count {
+            return Err(TextureViewRangeError::LayerOutOfRange {
+                layer: range.base_array_layer,
+                layer_count,
+            });
+        }
+        Ok(())
+    }
+
+    fn can_view(&self, range: &TextureViewRange, display: TextureDisplay) -> bool {
+        self.validate_view_range(range).is_ok() && self.validate_display(display).is_ok()
+    }
+
+    fn recreate_view(&mut self) {
+        let display = self.display();
+        if let Some((texture, view)) = self.texture_data.get_mut() {
+            *view = Self::create_display_view_raw(
+                texture,
+                self.label.as_deref(),
+                &self.view_range,
+                display,
+            );
+        }
+        *self.bind_group.get_mut() = None;
+    }
+
+    fn create_display_view_raw(
+        texture: &wgpu::Texture,
+        label: Option<&str>,
+        range: &TextureViewRange,
+        display: TextureDisplay,
+    ) -> wgpu::TextureView {
+        let dimension = display.view_dimension(range.base_sample_type(texture.format()));
+        let (base_array_layer, array_layer_count) = match dimension {
+            wgpu::TextureViewDimension::D2 => (range.base_array_layer, Some(1)),
+            wgpu::TextureViewDimension::Cube | wgpu::TextureViewDimension::D2Array => (0, Some(6)),
+            _ => (0, None),
+        };
+        texture.create_view(&wgpu::TextureViewDescriptor {
+            label,
+            dimension: Some(dimension),
+            aspect: range.resolve_aspect(texture.format()),
+            base_mip_level: range.base_mip_level,
+            mip_level_count: range.mip_level_count,
+            base_array_layer,
+            array_layer_count,
+            ..Default::default()
+        })
+    }
+
+    // Layer views are 2D, so they can't be created for 3D textures
+    fn layer_view_range(&self, layer: u32) -> Result<TextureViewRange, TextureViewRangeError> {
+        if self.texture_desc.dimension == wgpu::TextureDimension::D3 {
+            return Err(TextureViewRangeError::NotLayered(
+                self.texture_desc.dimension,
+            ));
+        }
+        let range = TextureViewRange {
+            base_array_layer: layer,
+            ..Default::default()
+        };
+        self.validate_view_range(&range)?;
+        Ok(range)
+    }
+
+    pub fn create_layer_view(
+        &self,
+        device: &wgpu::Device,
+        layer: u32,
+    ) -> Result<wgpu::TextureView, TextureViewRangeError> {
+        let range = self.layer_view_range(layer)?;
+        let mut texture_data = self.texture_data.borrow_mut();
+        let texture = &owned_texture_texture_data!(texture_data, self, device).0;
+        Ok(Self::create_display_view_raw(
+            texture,
+            self.label.as_deref(),
+            &range,
+            TextureDisplay::Layer,
+        ))
+    }
+
+    pub fn create_attachment_view(
+        &self,
+        device: &wgpu::Device,
+        layer: u32,
+    ) -> Result<wgpu::TextureView, TextureViewRangeError> {
+        self.layer_view_range(layer)?;
+        let mut texture_data = self.texture_data.borrow_mut();
+        let texture = &owned_texture_texture_data!(texture_data, self, device).0;
+        Ok(texture.create_view(&wgpu::TextureViewDescriptor {
+            label: self.label.as_deref(),
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            aspect: wgpu::TextureAspect::All,
+            base_mip_level: 0,
+            mip_level_count: Some(1),
+            base_array_layer: layer,
+            array_layer_count: Some(1),
+            ..Default::default()
+        }))
+    }
+
+    // The view keeps sampling the current allocation. Views added through
+    // `Renderer::add_texture_view_range` are rebuilt when this texture is recreated instead
+    #[track_caller]
+    pub fn create_texture_view(
+        &self,
+        device: &wgpu::Device,
+        label: Option<Cow<'static, str>>,
+        range: TextureViewRange,
+        display: TextureDisplay,
+    ) -> TextureView {
+        self.try_create_texture_view(device, label, range, display)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_create_texture_view(
+        &self,
+        device: &wgpu::Device,
+        label: Option<Cow<'static, str>>,
+        range: TextureViewRange,
+        display: TextureDisplay,
+    ) -> Result<TextureView, TextureViewError> {
+        self.validate_view_range(&range)?;
+        self.validate_display(display)?;
+        let display = self.resolve_display(&range, display);
+        let texture_view = {
+            let mut texture_data = self.texture_data.borrow_mut();
+            let texture = &owned_texture_texture_data!(texture_data, self, device).0;
+            Self::create_display_view_raw(texture, label.as_deref(), &range, display)
+        };
+        let mut texture = TextureView::new(
+            label,
+            texture_view,
+            range.sample_type(device, self.texture_desc.format, display),
+            self.sampler_desc.clone(),
+        );
+        texture.params.swizzle = self.params.swizzle;
+        texture.params.depth_display = self.params.depth_display;
+        texture.params.color_transform = self.params.color_transform;
+        texture.params.tone_mapping = self.params.tone_mapping;
+        texture.params.exposure = self.params.exposure;
+        texture.params.display = display;
+        Ok(texture)
+    }
+
+    pub fn sampler_desc(&self) -> &SamplerDescriptor {
+        &self.sampler_desc
+    }
+
+    pub fn set_sampler_desc(&mut self, value: SamplerDescriptor) {
+        self.sampler_desc = value;
+        *self.bind_group.get_mut() = None;
+    }
+
+    pub fn swizzle(&self) -> Swizzle {
+        self.params.swizzle
+    }
+
+    pub fn set_swizzle(&mut self, value: Swizzle) {
+        self.params.swizzle = value;
+        self.params.mark_changed();
+    }
+
+    pub fn depth_display(&self) -> &DepthDisplay {
+        &self.params.depth_display
+    }
+
+    #[track_caller]
+    pub fn set_depth_display(&mut self, value: DepthDisplay) {
+        self.try_set_depth_display(value)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    pub fn try_set_depth_display(&mut self, value: DepthDisplay) -> Result<(), DepthDisplayError> {
+        value.validate()?;
+        self.params.depth_display = value;
+        self.params.mark_changed();
+        Ok(())
+    }
+
+    pub fn color_transform(&self) -> &ColorTransform {
+        &self.params.color_transform
+    }
+
+    pub fn set_color_transform(&mut self, value: ColorTransform) {
+        self.params.color_transform = value;
+        self.params.mark_changed();
+    }
+
+    pub fn tone_mapping(&self) -> ToneMapping {
+        self.params.tone_mapping
+    }
+
+    pub fn set_tone_mapping(&mut self, value: ToneMapping) {
+        self.params.tone_mapping = value;
+        self.params.mark_changed();
+    }
+
+    // In stops, applied after the color transform
+    pub fn exposure(&self) -> f32 {
+        self.params.exposure
+    }
+
+    pub fn set_exposure(&mut self, value: f32) {
+        self.params.exposure = value;
+        self.params.mark_changed();
+    }
+
+    pub fn sample_type(&self, device: &wgpu::Device) -> wgpu::TextureSampleType {
+        self.view_range
+            .sample_type(device, self.texture_desc.format, self.display())
+    }
+
+    fn update_bind_group(&self, device: &wgpu::Device, renderer: &Renderer) {
+        let mut bind_group = self.bind_group.borrow_mut();
+        if bind_group.is_some() {
+            return;
+        }
+        let mut texture_data = self.texture_data.borrow_mut();
+        let texture_view = &owned_texture_texture_data!(texture_data, self, device).1;
+        let sample_type = self.sample_type(device);
+        let texture_pipeline = renderer.texture_pipeline(
+            device,
+            sample_type,
+            self.display().view_dimension(sample_type),
+        );
+        let sampler = (sample_type == wgpu::TextureSampleType::Float { filterable: true })
+            .then(|| renderer.sampler(device, &self.sampler_desc));
+        let mut params_buffer = self.params.buffer.borrow_mut();
+        let params_buffer = params_buffer.get_or_insert_with(|| {
+            self.params
+                .create_buffer(device, self.label.as_deref(), self.display())
+        });
+        *bind_group = Some(create_texture_bind_group(
+            device,
+            self.label.as_deref(),
+            &texture_pipeline.bind_group_layout,
+            texture_view,
+            sampler.as_deref(),
+            params_buffer,
+        ));
+    }
+
+    #[track_caller]
+    pub fn set_data(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        data: &[u8],
+        range: TextureSetRange,
+    ) {
+        self.try_set_data(device, queue, data, range)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    pub fn try_set_data(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        data: &[u8],
+        range: TextureSetRange,
+    ) -> Result<(), TextureDataError> {
+        let (extent, row_size, rows) = self.copy_layout(&range)?;
+        self.validate_data(&range, row_size, rows, data.len())?;
+        self.update_shadow(data, &range, extent);
+        let mut texture_data = self.texture_data.borrow_mut();
+        let texture = &owned_texture_texture_data!(texture_data, self, device).0;
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: range.mip_level,
+                origin: wgpu::Origin3d {
+                    x: range.x,
+                    y: range.y,
+                    z: range.layer,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::ImageDataLayout {
+                offset: range.offset,
+                bytes_per_row: self.texture_desc.bytes_per_row(range.mip_level),
+                rows_per_image: Some(rows),
+            },
+            extent,
+        );
+        Ok(())
+    }
+
+    // Keeps the shadow copy in sync with direct uploads, so that flushing it later doesn't
+    // overwrite them
+    fn update_shadow(&self, data: &[u8], range: &TextureSetRange, extent: wgpu::Extent3d) {
+        if range.mip_level != 0 {
+            return;
+        }
+        if let Some(shadow) = self.shadow.borrow_mut().as_mut() {
+            shadow.write(
+                DirtyRect::new(range, extent),
+                self.texture_desc.format.block_copy_size(None).unwrap(),
+                data,
+                range.offset,
+                self.texture_desc.bytes_per_row(0).unwrap(),
+            );
+        }
+    }
+
+    fn copy_layout(
+        &self,
+        range: &TextureSetRange,
+    ) -> Result<(wgpu::Extent3d, u32, u32), TextureDataError> {
+        let desc = &self.texture_desc;
+        let block_size = desc
+            .format
+            .block_copy_size(None)
+            .ok_or(TextureDataError::UnsupportedFormat(desc.format))?;
+        if range.mip_level >= desc.mip_level_count {
+            return Err(TextureDataError::MipLevelOutOfRange {
+                mip_level: range.mip_level,
+                mip_level_count: desc.mip_level_count,
+            });
+        }
+        if range.layer >= desc.layer_count(range.mip_level) {
+            return Err(TextureDataError::LayerOutOfRange {
+                layer: range.layer,
+                layer_count: desc.layer_count(range.mip_level),
+            });
+        }
+
+        let (width, height) = desc.mip_level_size(range.mip_level);
+        let extent = wgpu::Extent3d {
+            width: range.width.unwrap_or(width.saturating_sub(range.x)),
+            height: range.height.unwrap_or(height.saturating_sub(range.y)),
+            depth_or_array_layers: 1,
+        };
+        let (block_width, block_height) = desc.format.block_dimensions();
+        if !(range.x.is_multiple_of(block_width)
+            && range.y.is_multiple_of(block_height)
+            && extent.width.is_multiple_of(block_width)
+            && extent.height.is_multiple_of(block_height))
+        {
+            return Err(TextureDataError::UnalignedRange {
+                format: desc.format,
+                block_width,
+                block_height,
+            });
+        }
+        if range.x as u64 + extent.width as u64 > width as u64
+            || range.y as u64 + extent.height as u64 > height as u64
+        {
+            return Err(TextureDataError::RangeOutOfBounds {
+                mip_level: range.mip_level,
+                width,
+                height,
+            });
+        }
+
+        Ok((
+            extent,
+            extent.width / block_width * block_size,
+            extent.height / block_height,
+        ))
+    }
+
+    fn validate_data(
+        &self,
+        range: &TextureSetRange,
+        row_size: u32,
+        rows: u32,
+        len: usize,
+    ) -> Result<(), TextureDataError> {
+        let bytes_per_row = self.texture_desc.bytes_per_row(range.mip_level).unwrap();
+        self.validate_data_with_pitch(range, bytes_per_row, row_size, rows, len)
+    }
+
+    fn validate_data_with_pitch(
+        &self,
+        range: &TextureSetRange,
+        bytes_per_row: u32,
+        row_size: u32,
+        rows: u32,
+        len: usize,
+    ) -> Result<(), TextureDataError> {
+        let required = if rows == 0 {
+            range.offset
+        } else {
+            range.offset + bytes_per_row as u64 * (rows - 1) as u64 + row_size as u64
+        };
+        if (len as u64) < required {
+            return Err(TextureDataError::DataTooShort { required, len });
+        }
+        Ok(())
+    }
+
+    // Only copies from GPU buffers need aligned offsets, as `queue.write_texture` and the staging
+    // belt copy the data into buffers of their own
+    fn validate_buffer_offset(&self, offset: u64) -> Result<(), TextureDataError> {
+        let format = self.texture_desc.format;
+        let block_size = format.block_copy_size(None).unwrap();
+        let alignment = if format.is_depth_stencil_format() {
+            block_size.max(4)
+        } else {
+            block_size
+        };
+        if !offset.is_multiple_of(alignment as u64) {
+            return Err(TextureDataError::UnalignedOffset { offset, alignment });
+        }
+        Ok(())
+    }
+
+    fn create_shadow_copy(&self) -> ShadowCopy {
+        assert!(
+            self.texture_desc.supports_shadow_copy(),
+            "shadow copies are not supported for {:?} textures",
+            self.texture_desc.format
+        );
+        ShadowCopy::new(
+            self.texture_desc.bytes_per_row(0).unwrap(),
+            self.texture_desc.height,
+            self.texture_desc.depth_or_array_layers,
+        )
+    }
+
+    pub fn has_shadow_copy(&self) -> bool {
+        self.shadow.borrow().is_some()
+    }
+
+    pub fn set_shadow_copy(&mut self, value: bool) -> Result<(), TextureDataError> {
+        if value && !self.texture_desc.supports_shadow_copy() {
+            return Err(TextureDataError::UnsupportedFormat(
+                self.texture_desc.format,
+            ));
+        }
+        *self.shadow.get_mut() = value.then(|| self.create_shadow_copy());
+        Ok(())
+    }
+
+    pub fn shadow_data_mut(&mut self, layer: u32) -> Option<&mut [u8]> {
+        let shadow = self.shadow.get_mut().as_mut()?;
+        let range = shadow.layer_offset(layer)..shadow.layer_offset(layer.checked_add(1)?);
+        shadow.data.get_mut(range)
+    }
+
+    fn shadow_layout(
+        &self,
+        range: &TextureSetRange,
+    ) -> Result<(wgpu::Extent3d, u32, u32), TextureDataError> {
+        if self.shadow.borrow().is_none() {
+            return Err(TextureDataError::NoShadowCopy);
+        }
+        if range.mip_level != 0 {
+            return Err(TextureDataError::ShadowCopyMipLevel(range.mip_level));
+        }
+        self.copy_layout(range)
+    }
+
+    pub fn mark_dirty(&mut self, range: TextureSetRange) -> Result<(), TextureDataError> {
+        let (extent, _, _) = self.shadow_layout(&range)?;
+        let shadow = self.shadow.get_mut().as_mut().unwrap();
+        shadow.mark_dirty(DirtyRect::new(&range, extent));
+        Ok(())
+    }
+
+    // Writes only reach the GPU when the dirty rects are flushed, which `Renderer::render` does
+    // through `Renderer::flush_textures` before starting its render pass. Textures sampled
+    // outside of it need an explicit flush before use
+    pub fn write_shadow(
+        &mut self,
+        data: &[u8],
+        range: TextureSetRange,
+    ) -> Result<(), TextureDataError> {
+        let (extent, row_size, rows) = self.shadow_layout(&range)?;
+        self.validate_data(&range, row_size, rows, data.len())?;
+        let texel_size = self.texture_desc.format.block_copy_size(None).unwrap();
+        let bytes_per_row = self.texture_desc.bytes_per_row(0).unwrap();
+        let shadow = self.shadow.get_mut().as_mut().unwrap();
+        let rect = DirtyRect::new(&range, extent);
+        shadow.write(rect, texel_size, data, range.offset, bytes_per_row);
+        shadow.mark_dirty(rect);
+        Ok(())
+    }
+
+    pub fn dirty_rect_count(&self) -> usize {
+        self.shadow
+            .borrow()
+            .as_ref()
+            .map_or(0, ShadowCopy::dirty_rect_count)
+    }
+
+    pub fn flush_dirty_rects(&self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let mut shadow = self.shadow.borrow_mut();
+        let Some(shadow) = shadow.as_mut() else {
+            return;
+        };
+        let dirty_rects = shadow.take_dirty_rects();
+        if dirty_rects.is_empty() {
+            return;
+        }
+        let texel_size = self.texture_desc.format.block_copy_size(None).unwrap();
+        let mut texture_data = self.texture_data.borrow_mut();
+        let texture = &owned_texture_texture_data!(texture_data, self, device).0;
+        for rect in dirty_rects {
+            let offset = shadow.layer_offset(rect.layer)
+                + rect.y as usize * shadow.bytes_per_row as usize
+                + (rect.x * texel_size) as usize;
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: rect.x,
+                        y: rect.y,
+                        z: rect.layer,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &shadow.data,
+                wgpu::ImageDataLayout {
+                    offset: offset as u64,
+                    bytes_per_row: Some(shadow.bytes_per_row),
+                    rows_per_image: Some(rect.height),
+                },
+                wgpu::Extent3d {
+                    width: rect.width,
+                    height: rect.height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+    }
+
+    #[track_caller]
+    pub fn stream_data(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        staging_belt: &mut TextureStagingBelt,
+        data: &[u8],
+        range: TextureSetRange,
+    ) {
+        self.try_stream_data(device, encoder, staging_belt, data, range)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    pub fn try_stream_data(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        staging_belt: &mut TextureStagingBelt,
+        data: &[u8],
+        range: TextureSetRange,
+    ) -> Result<(), TextureDataError> {
+        let (extent, row_size, rows) = self.copy_layout(&range)?;
+        self.validate_data(&range, row_size, rows, data.len())?;
+        // Staging buffers can't be empty, and there's nothing to copy anyway
+        if row_size == 0 || rows == 0 {
+            return Ok(());
+        }
+        self.update_shadow(data, &range, extent);
+        let mut texture_data = self.texture_data.borrow_mut();
+        let texture = &owned_texture_texture_data!(texture_data, self, device).0;
+        staging_belt.write_texture(
+            device,
+            encoder,
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: range.mip_level,
+                origin: wgpu::Origin3d {
+                    x: range.x,
+                    y: range.y,
+                    z: range.layer,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            data,
+            wgpu::ImageDataLayout {
+                offset: range.offset,
+                bytes_per_row: self.texture_desc.bytes_per_row(range.mip_level),
+                rows_per_image: Some(rows),
+            },
+            row_size,
+            rows,
+            extent,
+        );
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn copy_from_buffer(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        buffer: &wgpu::Buffer,
+        bytes_per_row: u32,
+        range: TextureSetRange,
+    ) {
+        self.try_copy_from_buffer(device, encoder, buffer, bytes_per_row, range)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    // `bytes_per_row` is the row pitch of the buffer, which has to be a multiple of
+    // `wgpu::COPY_BYTES_PER_ROW_ALIGNMENT` unless a single row is copied
+    pub fn try_copy_from_buffer(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        buffer: &wgpu::Buffer,
+        bytes_per_row: u32,
+        range: TextureSetRange,
+    ) -> Result<(), TextureDataError> {
+        if !self
+            .texture_desc
+            .usage
+            .contains(wgpu::TextureUsages::COPY_DST)
+        {
+            return Err(TextureDataError::MissingUsage(
+                wgpu::TextureUsages::COPY_DST,
+            ));
+        }
+        if !buffer.usage().contains(wgpu::BufferUsages::COPY_SRC) {
+            return Err(TextureDataError::MissingBufferUsage(
+                wgpu::BufferUsages::COPY_SRC,
+            ));
+        }
+        let (extent, row_size, rows) = self.copy_layout(&range)?;
+        let layout_bytes_per_row = buffer_copy_bytes_per_row(bytes_per_row, row_size, rows)?;
+        let len = usize::try_from(buffer.size()).unwrap_or(usize::MAX);
+        self.validate_buffer_offset(range.offset)?;
+        self.validate_data_with_pitch(&range, bytes_per_row, row_size, rows, len)?;
+        if range.mip_level == 0 && self.shadow.borrow().is_some() {
+            return Err(TextureDataError::ShadowCopyNotUpdatable);
+        }
+
+        let mut texture_data = self.texture_data.borrow_mut();
+        let texture = &owned_texture_texture_data!(texture_data, self, device).0;
+        encoder.copy_buffer_to_texture(
+            wgpu::ImageCopyBuffer {
+                buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: range.offset,
+                    bytes_per_row: layout_bytes_per_row,
+                    rows_per_image: None,
+                },
+            },
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: range.mip_level,
+                origin: wgpu::Origin3d {
+                    x: range.x,
+                    y: range.y,
+                    z: range.layer,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            extent,
+        );
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn read_data<F>(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        range: TextureSetRange,
+        callback: F,
+    ) where
+        F: FnOnce(Result<Vec<u8>, wgpu::BufferAsyncError>) + wgpu::WasmNotSend + 'static,
+    {
+        self.try_read_data(device, queue, range, callback)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    pub fn try_read_data<F>(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        range: TextureSetRange,
+        callback: F,
+    ) -> Result<(), TextureDataError>
+    where
+        F: FnOnce(Result<Vec<u8>, wgpu::BufferAsyncError>) + wgpu::WasmNotSend + 'static,
+    {
+        let Some((extent, bytes_per_row, rows)) = self.read_layout(&range)? else {
+            callback(Ok(Vec::new()));
+            return Ok(());
+        };
+        let padded_bytes_per_row =
+            bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let buffer = Arc::new(device.create_buffer(&wgpu::BufferDescriptor {
+            label: self.label.as_deref(),
+            size: padded_bytes_per_row as u64 * rows as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        }));
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: self.label.as_deref(),
+        });
+        {
+            let mut texture_data = self.texture_data.borrow_mut();
+            let texture = &owned_texture_texture_data!(texture_data, self, device).0;
+            encoder.copy_texture_to_buffer(
+                wgpu::ImageCopyTexture {
+                    texture,
+                    mip_level: range.mip_level,
+                    origin: wgpu::Origin3d {
+                        x: range.x,
+                        y: range.y,
+                        z: range.layer,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::ImageCopyBuffer {
+                    buffer: &buffer,
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(padded_bytes_per_row),
+                        rows_per_image: Some(rows),
+                    },
+                },
+                extent,
+            );
+        }
+        queue.submit([encoder.finish()]);
+
+        let mapped_buffer = Arc::clone(&buffer);
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                callback(result.map(|()| {
+                    let mapped_range = mapped_buffer.slice(..).get_mapped_range();
+                    let mut data = Vec::with_capacity(bytes_per_row as usize * rows as usize);
+                    for row in mapped_range.chunks_exact(padded_bytes_per_row as usize) {
+                        data.extend_from_slice(&row[..bytes_per_row as usize]);
+                    }
+                    drop(mapped_range);
+                    mapped_buffer.unmap();
+                    data
+                }));
+            });
+        Ok(())
+    }
+
+    // Empty ranges are `None`, as they can't be mapped and are read back without touching the GPU
+    fn read_layout(
+        &self,
+        range: &TextureSetRange,
+    ) -> Result<Option<(wgpu::Extent3d, u32, u32)>, TextureDataError> {
+        if !self
+            .texture_desc
+            .usage
+            .contains(wgpu::TextureUsages::COPY_SRC)
+        {
+            return Err(TextureDataError::MissingUsage(
+                wgpu::TextureUsages::COPY_SRC,
+            ));
         }
+        let (extent, bytes_per_row, rows) = self.copy_layout(range)?;
+        Ok((bytes_per_row != 0 && rows != 0).then_some((extent, bytes_per_row, rows)))
     }
 
-    pub fn set_data(
+    fn generate_mipmaps(
         &self,
         device: &wgpu::Device,
-        queue: &wgpu::Queue,
-        data: &[u8],
-        range: TextureSetRange,
+        encoder: &mut wgpu::CommandEncoder,
+        generator: &MipmapGenerator,
     ) {
+        if self.texture_desc.mip_level_count <= 1 {
+            return;
+        }
+        assert!(
+            self.texture_desc
+                .usage
+                .contains(wgpu::TextureUsages::RENDER_ATTACHMENT),
+            "generating mipmaps requires the texture to have RENDER_ATTACHMENT usage"
+        );
+        assert!(
+            self.texture_desc.dimension == wgpu::TextureDimension::D2,
+            "generating mipmaps is only supported for 2D textures"
+        );
+        assert!(
+            MipmapGenerator::supports_format(device, self.texture_desc.format),
+            "generating mipmaps is not supported for {:?} textures",
+            self.texture_desc.format
+        );
         let mut texture_data = self.texture_data.borrow_mut();
         let texture = &owned_texture_texture_data!(texture_data, self, device).0;
-        queue.write_texture(
-            wgpu::ImageCopyTexture {
-                texture,
-                mip_level: range.mip_level,
-                origin: wgpu::Origin3d {
-                    x: range.x,
-                    y: range.y,
-                    z: 0,
-                },
-                aspect: wgpu::TextureAspect::All,
-            },
-            data,
-            wgpu::ImageDataLayout {
-                offset: range.offset,
-                bytes_per_row: self.texture_bytes_per_row,
-                rows_per_image: None,
-            },
-            wgpu::Extent3d {
-                width: range.width.unwrap_or(self.texture_desc.width),
-                height: range.height.unwrap_or(self.texture_desc.height),
-                depth_or_array_layers: 1,
-            },
-        );
+        generator.generate(device, encoder, texture);
     }
 }
 
 pub struct TextureView {
     label: Option<Cow<'static, str>>,
     texture_view: wgpu::TextureView,
+    sample_type: wgpu::TextureSampleType,
     sampler_desc: SamplerDescriptor,
-    sampler: RefCell<Option<wgpu::Sampler>>,
+    params: TextureParams,
     bind_group: RefCell<Option<wgpu::BindGroup>>,
+    source: Option<ViewSource>,
+}
+
+struct ViewSource {
+    texture_id: imgui::TextureId,
+    range: TextureViewRange,
+    texture_generation: u64,
+    explicit_sample_type: bool,
 }
 
 impl TextureView {
@@ -257,14 +1944,17 @@ impl TextureView {
     fn new(
         label: Option<Cow<'static, str>>,
         texture_view: wgpu::TextureView,
+        sample_type: wgpu::TextureSampleType,
         sampler_desc: SamplerDescriptor,
     ) -> Self {
         TextureView {
             label,
             texture_view,
+            sample_type,
             sampler_desc,
-            sampler: RefCell::new(None),
+            params: TextureParams::new(),
             bind_group: RefCell::new(None),
+            source: None,
         }
     }
 
@@ -274,7 +1964,7 @@ impl TextureView {
 
     pub fn set_label(&mut self, value: Option<Cow<'static, str>>) {
         self.label = value;
-        *self.sampler.get_mut() = None;
+        *self.params.buffer.get_mut() = None;
         *self.bind_group.get_mut() = None;
     }
 
@@ -283,42 +1973,152 @@ impl TextureView {
     }
 
     pub fn set_texture_view(&mut self, value: wgpu::TextureView) -> wgpu::TextureView {
+        self.source = None;
         *self.bind_group.get_mut() = None;
         replace(&mut self.texture_view, value)
     }
 
+    pub fn sample_type(&self) -> wgpu::TextureSampleType {
+        self.sample_type
+    }
+
+    #[track_caller]
+    pub fn set_sample_type(&mut self, value: wgpu::TextureSampleType) {
+        self.try_set_sample_type(value)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    pub fn try_set_sample_type(
+        &mut self,
+        value: wgpu::TextureSampleType,
+    ) -> Result<(), TextureDisplayError> {
+        Self::validate_display(value, self.params.display)?;
+        self.sample_type = value;
+        if let Some(source) = &mut self.source {
+            source.explicit_sample_type = true;
+        }
+        *self.bind_group.get_mut() = None;
+        Ok(())
+    }
+
+    pub fn display(&self) -> TextureDisplay {
+        self.params.display
+    }
+
+    #[track_caller]
+    pub fn set_display(&mut self, value: TextureDisplay) {
+        self.try_set_display(value)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    pub fn try_set_display(&mut self, value: TextureDisplay) -> Result<(), TextureDisplayError> {
+        Self::validate_display(self.sample_type, value)?;
+        self.params.display = value;
+        self.params.mark_changed();
+        *self.bind_group.get_mut() = None;
+        Ok(())
+    }
+
+    fn validate_display(
+        sample_type: wgpu::TextureSampleType,
+        display: TextureDisplay,
+    ) -> Result<(), TextureDisplayError> {
+        if matches!(display, TextureDisplay::Slice(_))
+            && sample_type == wgpu::TextureSampleType::Depth
+        {
+            return Err(TextureDisplayError::DepthVolume);
+        }
+        Ok(())
+    }
+
     pub fn sampler_desc(&self) -> &SamplerDescriptor {
         &self.sampler_desc
     }
 
     pub fn set_sampler_desc(&mut self, value: SamplerDescriptor) {
         self.sampler_desc = value;
-        *self.sampler.get_mut() = None;
         *self.bind_group.get_mut() = None;
     }
 
-    fn update_bind_group(&self, device: &wgpu::Device, bind_group_layout: &wgpu::BindGroupLayout) {
-        let mut sampler = self.sampler.borrow_mut();
-        let sampler = sampler.get_or_insert_with(|| {
-            device.create_sampler(&self.sampler_desc.to_raw(self.label.as_deref()))
-        });
+    pub fn swizzle(&self) -> Swizzle {
+        self.params.swizzle
+    }
+
+    pub fn set_swizzle(&mut self, value: Swizzle) {
+        self.params.swizzle = value;
+        self.params.mark_changed();
+    }
+
+    pub fn depth_display(&self) -> &DepthDisplay {
+        &self.params.depth_display
+    }
+
+    #[track_caller]
+    pub fn set_depth_display(&mut self, value: DepthDisplay) {
+        self.try_set_depth_display(value)
+            .unwrap_or_else(|err| panic!("{err}"));
+    }
+
+    pub fn try_set_depth_display(&mut self, value: DepthDisplay) -> Result<(), DepthDisplayError> {
+        value.validate()?;
+        self.params.depth_display = value;
+        self.params.mark_changed();
+        Ok(())
+    }
+
+    pub fn color_transform(&self) -> &ColorTransform {
+        &self.params.color_transform
+    }
+
+    pub fn set_color_transform(&mut self, value: ColorTransform) {
+        self.params.color_transform = value;
+        self.params.mark_changed();
+    }
+
+    pub fn tone_mapping(&self) -> ToneMapping {
+        self.params.tone_mapping
+    }
+
+    pub fn set_tone_mapping(&mut self, value: ToneMapping) {
+        self.params.tone_mapping = value;
+        self.params.mark_changed();
+    }
+
+    // In stops, applied after the color transform
+    pub fn exposure(&self) -> f32 {
+        self.params.exposure
+    }
+
+    pub fn set_exposure(&mut self, value: f32) {
+        self.params.exposure = value;
+        self.params.mark_changed();
+    }
+
+    fn update_bind_group(&self, device: &wgpu::Device, renderer: &Renderer) {
         let mut bind_group = self.bind_group.borrow_mut();
-        if bind_group.is_none() {
-            *bind_group = Some(device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: self.label.as_deref(),
-                layout: bind_group_layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureView(&self.texture_view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::Sampler(sampler),
-                    },
-                ],
-            }));
+        if bind_group.is_some() {
+            return;
         }
+        let texture_pipeline = renderer.texture_pipeline(
+            device,
+            self.sample_type,
+            self.params.display.view_dimension(self.sample_type),
+        );
+        let sampler = (self.sample_type == wgpu::TextureSampleType::Float { filterable: true })
+            .then(|| renderer.sampler(device, &self.sampler_desc));
+        let mut params_buffer = self.params.buffer.borrow_mut();
+        let params_buffer = params_buffer.get_or_insert_with(|| {
+            self.params
+                .create_buffer(device, self.label.as_deref(), self.params.display)
+        });
+        *bind_group = Some(create_texture_bind_group(
+            device,
+            self.label.as_deref(),
+            &texture_pipeline.bind_group_layout,
+            &self.texture_view,
+            sampler.as_deref(),
+            params_buffer,
+        ));
     }
 }
 
@@ -329,37 +2129,63 @@ pub enum Texture {
 }
 
 macro_rules! unwrap {
-    ($($fn: ident, $self: ty, $variant: ident, $ty: ty);*) => {
+    ($($fn: ident, $as_fn: ident, $self: ty, $variant: ident, $ty: ty, $msg: literal);*) => {
         $(
-            pub fn $fn(self: $self) -> $ty {
+            #[inline]
+            pub fn $as_fn(self: $self) -> Option<$ty> {
                 match self {
-                    Texture::$variant(v) => v,
-                    _ => panic!(),
+                    Texture::$variant(v) => Some(v),
+                    _ => None,
                 }
             }
+
+            #[inline]
+            #[track_caller]
+            pub fn $fn(self: $self) -> $ty {
+                self.$as_fn().expect($msg)
+            }
         )*
     };
 }
 
 impl Texture {
     unwrap!(
-        unwrap_owned, Self, Owned, OwnedTexture;
-        unwrap_owned_ref, &Self, Owned, &OwnedTexture;
-        unwrap_owned_mut, &mut Self, Owned, &mut OwnedTexture;
-        unwrap_view, Self, View, TextureView;
-        unwrap_view_ref, &Self, View, &TextureView;
-        unwrap_view_mut, &mut Self, View, &mut TextureView
+        unwrap_owned, into_owned, Self, Owned, OwnedTexture, "texture is not an owned texture";
+        unwrap_owned_ref, as_owned, &Self, Owned, &OwnedTexture, "texture is not an owned texture";
+        unwrap_owned_mut, as_owned_mut, &mut Self, Owned, &mut OwnedTexture,
+            "texture is not an owned texture";
+        unwrap_view, into_view, Self, View, TextureView, "texture is not a texture view";
+        unwrap_view_ref, as_view, &Self, View, &TextureView, "texture is not a texture view";
+        unwrap_view_mut, as_view_mut, &mut Self, View, &mut TextureView,
+            "texture is not a texture view"
     );
 
-    pub fn bind_group(
-        &self,
-        device: &wgpu::Device,
-        bind_group_layout: &wgpu::BindGroupLayout,
-    ) -> &wgpu::BindGroup {
+    pub fn sample_type(&self, device: &wgpu::Device) -> wgpu::TextureSampleType {
+        match self {
+            Texture::Owned(texture) => texture.sample_type(device),
+            Texture::View(texture) => texture.sample_type(),
+        }
+    }
+
+    pub fn display(&self) -> TextureDisplay {
+        match self {
+            Texture::Owned(texture) => texture.display(),
+            Texture::View(texture) => texture.display(),
+        }
+    }
+
+    pub fn memory_size(&self) -> Option<u64> {
+        match self {
+            Texture::Owned(texture) => texture.memory_size(),
+            Texture::View(_) => None,
+        }
+    }
+
+    pub fn bind_group(&self, device: &wgpu::Device, renderer: &Renderer) -> &wgpu::BindGroup {
         unsafe {
             match self {
                 Texture::Owned(texture) => {
-                    texture.update_bind_group(device, bind_group_layout);
+                    texture.update_bind_group(device, renderer);
                     texture
                         .bind_group
                         .try_borrow_unguarded()
@@ -368,7 +2194,7 @@ impl Texture {
                         .unwrap_unchecked()
                 }
                 Texture::View(texture) => {
-                    texture.update_bind_group(device, bind_group_layout);
+                    texture.update_bind_group(device, renderer);
                     texture
                         .bind_group
                         .try_borrow_unguarded()
@@ -381,6 +2207,45 @@ impl Texture {
     }
 }
 
+#[derive(Debug)]
+pub struct TextureHandle {
+    id: imgui::TextureId,
+    registry: Weak<SharedRegistry>,
+}
+
+impl TextureHandle {
+    pub fn id(&self) -> imgui::TextureId {
+        self.id
+    }
+
+    pub fn into_id(mut self) -> imgui::TextureId {
+        self.registry = Weak::new();
+        self.id
+    }
+}
+
+impl From<&TextureHandle> for imgui::TextureId {
+    fn from(handle: &TextureHandle) -> Self {
+        handle.id
+    }
+}
+
+impl Drop for TextureHandle {
+    fn drop(&mut self) {
+        if let Some(registry) = self.registry.upgrade() {
+            registry.drop_texture(self.id);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MissingTextureFallback {
+    #[default]
+    Skip,
+    Checkerboard,
+    Texture(imgui::TextureId),
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SrgbMode {
     None,
@@ -389,19 +2254,177 @@ pub enum SrgbMode {
     Srgb,
 }
 
+struct TexturePipeline {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    shader_module: wgpu::ShaderModule,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl TexturePipeline {
+    #[must_use]
+    fn new(
+        device: &wgpu::Device,
+        view_bind_group_layout: &wgpu::BindGroupLayout,
+        sample_type: wgpu::TextureSampleType,
+        view_dimension: wgpu::TextureViewDimension,
+        output_format: wgpu::TextureFormat,
+        srgb_mode: SrgbMode,
+    ) -> Self {
+        let filterable = sample_type == wgpu::TextureSampleType::Float { filterable: true };
+        let mut entries = vec![
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    // Depth formats may be bound as unfilterable floats, which lets the depth
+                    // shaders read them with `textureLoad` like any other `texture_2d<f32>`
+                    sample_type: if sample_type == wgpu::TextureSampleType::Depth {
+                        wgpu::TextureSampleType::Float { filterable: false }
+                    } else {
+                        sample_type
+                    },
+                    view_dimension,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+        ];
+        if filterable {
+            entries.push(wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            });
+        }
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("imgui texture"),
+            entries: &entries,
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("ImGui"),
+            bind_group_layouts: &[view_bind_group_layout, &bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let load_type = match sample_type {
+            wgpu::TextureSampleType::Uint => "u32",
+            wgpu::TextureSampleType::Sint => "i32",
+            _ => "f32",
+        };
+        let sample_source = match (sample_type, view_dimension) {
+            (_, wgpu::TextureViewDimension::D2) if filterable => {
+                include_str!("imgui-sample.wgsl").into()
+            }
+            (_, wgpu::TextureViewDimension::Cube) if filterable => {
+                include_str!("imgui-sample-cube.wgsl").into()
+            }
+            (_, wgpu::TextureViewDimension::D3) if filterable => {
+                include_str!("imgui-sample-3d.wgsl").into()
+            }
+            (wgpu::TextureSampleType::Depth, wgpu::TextureViewDimension::D2) => {
+                include_str!("imgui-load-depth.wgsl").into()
+            }
+            (wgpu::TextureSampleType::Depth, wgpu::TextureViewDimension::D2Array) => {
+                include_str!("imgui-load-depth-cube.wgsl").into()
+            }
+            (wgpu::TextureSampleType::Depth, _) | (_, wgpu::TextureViewDimension::Cube) => {
+                panic!(
+                    "displaying {view_dimension:?} views is not supported for {sample_type:?} \
+                     textures"
+                )
+            }
+            (_, wgpu::TextureViewDimension::D2) => {
+                include_str!("imgui-load.wgsl").replace("SAMPLE_TYPE", load_type)
+            }
+            (_, wgpu::TextureViewDimension::D2Array) => {
+                include_str!("imgui-load-cube.wgsl").replace("SAMPLE_TYPE", load_type)
+            }
+            (_, wgpu::TextureViewDimension::D3) => {
+                include_str!("imgui-load-3d.wgsl").replace("SAMPLE_TYPE", load_type)
+            }
+            _ => panic!("displaying {view_dimension:?} views is not supported"),
+        };
+        let output_source = match srgb_mode {
+            SrgbMode::None => include_str!("imgui-output.wgsl"),
+            SrgbMode::Linear => include_str!("imgui-output-linear.wgsl"),
+            SrgbMode::Srgb => include_str!("imgui-output-srgb.wgsl"),
+        };
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("ImGui"),
+            source: wgpu::ShaderSource::Wgsl(
+                [include_str!("imgui.wgsl"), &sample_source, output_source]
+                    .concat()
+                    .into(),
+            ),
+        });
+
+        let pipeline = Renderer::rebuild_pipeline(
+            device,
+            &pipeline_layout,
+            &shader_module,
+            output_format,
+            srgb_mode,
+        );
+
+        TexturePipeline {
+            bind_group_layout,
+            pipeline_layout,
+            shader_module,
+            pipeline,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RendererStats {
+    pub texture_count: usize,
+    pub view_count: usize,
+    pub created_texture_count: usize,
+    pub pending_texture_count: usize,
+    pub texture_memory: u64,
+    pub pending_texture_memory: u64,
+    pub unsized_texture_count: usize,
+    pub vtx_buffer_capacity: u64,
+    pub idx_buffer_capacity: u64,
+}
+
+impl RendererStats {
+    pub fn total_memory(&self) -> u64 {
+        self.texture_memory + self.vtx_buffer_capacity + self.idx_buffer_capacity
+    }
+}
+
 pub struct Renderer {
     view_buffer: wgpu::Buffer,
+    view_bind_group_layout: wgpu::BindGroupLayout,
     view_bind_group: wgpu::BindGroup,
-    texture_bind_group_layout: wgpu::BindGroupLayout,
     vtx_buffer: Option<wgpu::Buffer>,
     vtx_buffer_capacity: u64,
     idx_buffer: Option<wgpu::Buffer>,
     idx_buffer_capacity: u64,
-    pipeline_layout: wgpu::PipelineLayout,
-    shader_module: wgpu::ShaderModule,
-    pipeline: wgpu::RenderPipeline,
-    textures: RefCell<HashMap<imgui::TextureId, Texture>>,
-    next_texture_id: Cell<usize>,
+    output_format: wgpu::TextureFormat,
+    texture_pipelines:
+        RefCell<HashMap<(wgpu::TextureSampleType, wgpu::TextureViewDimension), TexturePipeline>>,
+    samplers: RefCell<HashMap<SamplerDescriptor, wgpu::Sampler>>,
+    mipmap_generator: OnceCell<MipmapGenerator>,
+    textures: RefCell<TextureRegistry>,
+    missing_texture_fallback: MissingTextureFallback,
+    checkerboard_texture: OnceCell<Texture>,
+    log_missing_textures: bool,
+    missing_texture_ids: HashSet<imgui::TextureId>,
+    reported_missing_texture_ids: HashSet<imgui::TextureId>,
     srgb_mode: SrgbMode,
 }
 
@@ -532,61 +2555,34 @@ impl Renderer {
                 }),
             }],
         });
-        let texture_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("imgui texture"),
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            multisampled: false,
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                ],
-            });
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("ImGui"),
-            bind_group_layouts: &[&view_bind_group_layout, &texture_bind_group_layout],
-            push_constant_ranges: &[],
-        });
-        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("ImGui"),
-            source: wgpu::ShaderSource::Wgsl(
-                match srgb_mode {
-                    SrgbMode::None => include_str!("imgui.wgsl"),
-                    SrgbMode::Linear => include_str!("imgui-linear.wgsl"),
-                    SrgbMode::Srgb => include_str!("imgui-srgb.wgsl"),
-                }
-                .into(),
+        let default_sample_type = wgpu::TextureSampleType::Float { filterable: true };
+        let mut texture_pipelines = HashMap::with_capacity(1);
+        texture_pipelines.insert(
+            (default_sample_type, wgpu::TextureViewDimension::D2),
+            TexturePipeline::new(
+                device,
+                &view_bind_group_layout,
+                default_sample_type,
+                wgpu::TextureViewDimension::D2,
+                output_format,
+                srgb_mode,
             ),
-        });
-        let pipeline = Self::rebuild_pipeline(
-            device,
-            &pipeline_layout,
-            &shader_module,
-            output_format,
-            srgb_mode,
         );
 
         let mut renderer = Renderer {
             view_buffer,
+            view_bind_group_layout,
             view_bind_group,
-            texture_bind_group_layout,
-            pipeline_layout,
-            shader_module,
-            pipeline,
-            textures: RefCell::new(HashMap::with_capacity(1)),
-            next_texture_id: Cell::new(1),
+            output_format,
+            texture_pipelines: RefCell::new(texture_pipelines),
+            samplers: RefCell::new(HashMap::new()),
+            mipmap_generator: OnceCell::new(),
+            textures: RefCell::new(TextureRegistry::new()),
+            missing_texture_fallback: MissingTextureFallback::Skip,
+            checkerboard_texture: OnceCell::new(),
+            log_missing_textures: true,
+            missing_texture_ids: HashSet::new(),
+            reported_missing_texture_ids: HashSet::new(),
             vtx_buffer: None,
             vtx_buffer_capacity: 0,
             idx_buffer: None,
@@ -599,46 +2595,200 @@ impl Renderer {
         renderer
     }
 
-    #[inline]
-    pub fn change_swapchain_format(&mut self, device: &wgpu::Device, format: wgpu::TextureFormat) {
-        self.pipeline = Self::rebuild_pipeline(
-            device,
-            &self.pipeline_layout,
-            &self.shader_module,
-            format,
-            self.srgb_mode,
-        );
+    #[inline]
+    pub fn change_swapchain_format(&mut self, device: &wgpu::Device, format: wgpu::TextureFormat) {
+        self.output_format = format;
+        for texture_pipeline in self.texture_pipelines.get_mut().values_mut() {
+            texture_pipeline.pipeline = Self::rebuild_pipeline(
+                device,
+                &texture_pipeline.pipeline_layout,
+                &texture_pipeline.shader_module,
+                format,
+                self.srgb_mode,
+            );
+        }
+    }
+
+    fn texture_pipeline(
+        &self,
+        device: &wgpu::Device,
+        sample_type: wgpu::TextureSampleType,
+        view_dimension: wgpu::TextureViewDimension,
+    ) -> Ref<'_, TexturePipeline> {
+        let key = (sample_type, view_dimension);
+        self.texture_pipelines
+            .borrow_mut()
+            .entry(key)
+            .or_insert_with(|| {
+                TexturePipeline::new(
+                    device,
+                    &self.view_bind_group_layout,
+                    sample_type,
+                    view_dimension,
+                    self.output_format,
+                    self.srgb_mode,
+                )
+            });
+        Ref::map(self.texture_pipelines.borrow(), |texture_pipelines| {
+            &texture_pipelines[&key]
+        })
+    }
+
+    fn sampler(&self, device: &wgpu::Device, desc: &SamplerDescriptor) -> Ref<'_, wgpu::Sampler> {
+        if !self.samplers.borrow().contains_key(desc) {
+            self.samplers.borrow_mut().insert(
+                desc.clone(),
+                device.create_sampler(&desc.to_raw(Some("imgui sampler"))),
+            );
+        }
+        Ref::map(self.samplers.borrow(), |samplers| &samplers[desc])
+    }
+
+    pub fn sampler_count(&self) -> usize {
+        self.samplers.borrow().len()
+    }
+
+    #[inline]
+    pub fn add_texture(&self, texture: Texture) -> imgui::TextureId {
+        self.register_pending_textures();
+        self.remove_dropped_textures();
+        self.textures.borrow_mut().insert(texture)
+    }
+
+    #[inline]
+    pub fn add_texture_with_handle(&self, texture: Texture) -> TextureHandle {
+        let id = self.add_texture(texture);
+        self.texture_handle(id)
+    }
+
+    #[inline]
+    pub fn texture_handle(&self, id: imgui::TextureId) -> TextureHandle {
+        self.textures.borrow().handle().texture_handle(id)
+    }
+
+    #[inline]
+    pub fn registry_handle(&self) -> TextureRegistryHandle {
+        self.textures.borrow().handle()
+    }
+
+    pub fn register_pending_textures(&self) {
+        if self.textures.borrow().has_pending() {
+            self.textures.borrow_mut().register_pending();
+        }
+    }
+
+    pub fn remove_dropped_textures(&self) {
+        if self.textures.borrow().has_dropped() {
+            self.textures.borrow_mut().remove_dropped();
+        }
+    }
+
+    #[inline]
+    pub fn create_owned_texture(
+        &self,
+        label: Option<Cow<'static, str>>,
+        texture_desc: TextureDescriptor,
+        sampler_desc: SamplerDescriptor,
+    ) -> OwnedTexture {
+        OwnedTexture::new(label, texture_desc, sampler_desc)
+    }
+
+    #[inline]
+    pub fn create_and_add_owned_texture(
+        &self,
+        label: Option<Cow<'static, str>>,
+        texture_desc: TextureDescriptor,
+        sampler_desc: SamplerDescriptor,
+    ) -> imgui::TextureId {
+        let texture = self.create_owned_texture(label, texture_desc, sampler_desc);
+        self.add_texture(Texture::Owned(texture))
+    }
+
+    pub fn try_add_texture_view_range(
+        &self,
+        device: &wgpu::Device,
+        id: imgui::TextureId,
+        range: TextureViewRange,
+        display: TextureDisplay,
+    ) -> Result<imgui::TextureId, TextureError> {
+        let texture = match &*self.try_texture(id)? {
+            Texture::Owned(texture) => {
+                let mut view = texture
+                    .try_create_texture_view(device, None, range, display)
+                    .map_err(|err| TextureError::InvalidView(id, err))?;
+                view.source = Some(ViewSource {
+                    texture_id: id,
+                    range,
+                    texture_generation: texture.texture_generation.get(),
+                    explicit_sample_type: false,
+                });
+                view
+            }
+            Texture::View(_) => return Err(TextureError::NotOwned(id)),
+        };
+        Ok(self.add_texture(Texture::View(texture)))
     }
 
     #[inline]
-    pub fn add_texture(&self, texture: Texture) -> imgui::TextureId {
-        let id = self.next_texture_id.get();
-        self.next_texture_id.set(id + 1);
-        self.textures.borrow_mut().insert(id.into(), texture);
-        id.into()
+    #[track_caller]
+    pub fn add_texture_view_range(
+        &self,
+        device: &wgpu::Device,
+        id: imgui::TextureId,
+        range: TextureViewRange,
+        display: TextureDisplay,
+    ) -> imgui::TextureId {
+        self.try_add_texture_view_range(device, id, range, display)
+            .unwrap_or_else(|err| panic!("{err}"))
     }
 
     #[inline]
-    pub fn create_owned_texture(
+    pub fn create_render_target(
         &self,
         label: Option<Cow<'static, str>>,
-        texture_desc: TextureDescriptor,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
         sampler_desc: SamplerDescriptor,
     ) -> OwnedTexture {
-        OwnedTexture::new(label, texture_desc, sampler_desc)
+        self.create_owned_texture(
+            label,
+            TextureDescriptor {
+                width: width.max(1),
+                height: height.max(1),
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING,
+                ..Default::default()
+            },
+            sampler_desc,
+        )
     }
 
     #[inline]
-    pub fn create_and_add_owned_texture(
+    pub fn create_and_add_render_target(
         &self,
         label: Option<Cow<'static, str>>,
-        texture_desc: TextureDescriptor,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
         sampler_desc: SamplerDescriptor,
     ) -> imgui::TextureId {
-        let texture = self.create_owned_texture(label, texture_desc, sampler_desc);
+        let texture = self.create_render_target(label, width, height, format, sampler_desc);
         self.add_texture(Texture::Owned(texture))
     }
 
+    #[inline]
+    pub fn create_texture_atlas(
+        &self,
+        label: Option<Cow<'static, str>>,
+        page_size: u32,
+        format: wgpu::TextureFormat,
+        sampler_desc: SamplerDescriptor,
+    ) -> TextureAtlas {
+        TextureAtlas::new(label, page_size, format, sampler_desc)
+    }
+
     #[inline]
     pub fn create_texture_view(
         &self,
@@ -646,7 +2796,7 @@ impl Renderer {
         texture_view: wgpu::TextureView,
         sampler_desc: SamplerDescriptor,
     ) -> TextureView {
-        TextureView::new(label, texture_view, sampler_desc)
+        TextureView::new(label, texture_view, FILTERABLE_FLOAT, sampler_desc)
     }
 
     #[inline]
@@ -660,23 +2810,280 @@ impl Renderer {
         self.add_texture(Texture::View(texture))
     }
 
+    // Views of non-filterable, integer or depth formats need their sample type spelled out, as it
+    // can't be recovered from a `wgpu::TextureView`
+    #[inline]
+    pub fn create_texture_view_with_sample_type(
+        &self,
+        label: Option<Cow<'static, str>>,
+        texture_view: wgpu::TextureView,
+        sample_type: wgpu::TextureSampleType,
+        sampler_desc: SamplerDescriptor,
+    ) -> TextureView {
+        TextureView::new(label, texture_view, sample_type, sampler_desc)
+    }
+
+    #[inline]
+    pub fn create_and_add_texture_view_with_sample_type(
+        &self,
+        label: Option<Cow<'static, str>>,
+        texture_view: wgpu::TextureView,
+        sample_type: wgpu::TextureSampleType,
+        sampler_desc: SamplerDescriptor,
+    ) -> imgui::TextureId {
+        let texture = self.create_texture_view_with_sample_type(
+            label,
+            texture_view,
+            sample_type,
+            sampler_desc,
+        );
+        self.add_texture(Texture::View(texture))
+    }
+
     #[inline]
     pub fn remove_texture(&self, id: imgui::TextureId) -> Option<Texture> {
-        self.textures.borrow_mut().remove(&id)
+        let mut textures = self.textures.borrow_mut();
+        textures.register_pending();
+        textures.remove(id)
+    }
+
+    #[inline]
+    pub fn texture_count(&self) -> usize {
+        self.registered_textures().len()
+    }
+
+    pub fn stats(&self) -> RendererStats {
+        let mut stats = RendererStats {
+            vtx_buffer_capacity: self.vtx_buffer.as_ref().map_or(0, wgpu::Buffer::size),
+            idx_buffer_capacity: self.idx_buffer.as_ref().map_or(0, wgpu::Buffer::size),
+            ..Default::default()
+        };
+        let textures = self.registered_textures();
+        for texture in textures.iter() {
+            stats.texture_count += 1;
+            let Texture::Owned(texture) = texture else {
+                stats.view_count += 1;
+                continue;
+            };
+            // Textures that can't be sized are counted instead of being guessed at
+            let memory_size = texture.memory_size().unwrap_or_else(|| {
+                stats.unsized_texture_count += 1;
+                0
+            });
+            if texture.is_created() {
+                stats.created_texture_count += 1;
+                stats.texture_memory += memory_size;
+            } else {
+                stats.pending_texture_count += 1;
+                stats.pending_texture_memory += memory_size;
+            }
+        }
+        // The placeholder isn't registered, but it still occupies memory once created
+        if let Some(Texture::Owned(texture)) = self.checkerboard_texture.get() {
+            stats.texture_memory += texture.memory_size().unwrap_or(0);
+        }
+        stats
+    }
+
+    // Uploads dirty rects and display parameter changes. `render` does this on its own, but
+    // textures drawn through `Texture::bind_group` elsewhere need it first
+    pub fn flush_textures(&self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        for texture in self.registered_textures().iter() {
+            match texture {
+                Texture::Owned(texture) => {
+                    texture.flush_dirty_rects(device, queue);
+                    texture.params.flush(queue, texture.display());
+                }
+                Texture::View(texture) => texture.params.flush(queue, texture.params.display),
+            }
+        }
+    }
+
+    // Views added through `add_texture_view_range` would otherwise keep sampling the allocation
+    // their texture had before it was resized or recreated
+    fn rebuild_derived_views(&mut self, device: &wgpu::Device) {
+        let textures = self.textures.get_mut();
+        let mut rebuilt_views = Vec::new();
+        for (id, texture) in textures.iter_with_ids() {
+            let Texture::View(view) = texture else {
+                continue;
+            };
+            let Some(source) = &view.source else {
+                continue;
+            };
+            let Some(Texture::Owned(owned)) = textures.get(source.texture_id) else {
+                continue;
+            };
+            let display = view.display();
+            let texture = owned.texture(device);
+            let texture_generation = owned.texture_generation.get();
+            if texture_generation == source.texture_generation {
+                continue;
+            }
+            let rebuilt = if owned.can_view(&source.range, display) {
+                let texture_view = OwnedTexture::create_display_view_raw(
+                    &texture,
+                    view.label.as_deref(),
+                    &source.range,
+                    display,
+                );
+                let format = owned.texture_desc.format;
+                Some((
+                    texture_view,
+                    source.range.sample_type(device, format, display),
+                ))
+            } else {
+                log::warn!(
+                    "texture {id:?} no longer fits texture {:?}, keeping its old view",
+                    source.texture_id,
+                );
+                None
+            };
+            rebuilt_views.push((id, texture_generation, rebuilt));
+        }
+        for (id, texture_generation, rebuilt) in rebuilt_views {
+            let Some(Texture::View(view)) = textures.get_mut(id) else {
+                continue;
+            };
+            let source = view.source.as_mut().unwrap();
+            source.texture_generation = texture_generation;
+            if let Some((texture_view, sample_type)) = rebuilt {
+                view.texture_view = texture_view;
+                if !source.explicit_sample_type {
+                    view.sample_type = sample_type;
+                }
+                *view.bind_group.get_mut() = None;
+            }
+        }
+    }
+
+    #[inline]
+    pub fn contains_texture(&self, id: imgui::TextureId) -> bool {
+        self.registered_textures().lookup(id) == Lookup::Present
+    }
+
+    #[inline]
+    pub fn is_texture_stale(&self, id: imgui::TextureId) -> bool {
+        self.registered_textures().lookup(id) == Lookup::Stale
+    }
+
+    pub fn try_texture(&self, id: imgui::TextureId) -> Result<Ref<'_, Texture>, TextureError> {
+        self.try_register_pending_textures();
+        let textures = self
+            .textures
+            .try_borrow()
+            .map_err(|_| TextureError::Borrowed(id))?;
+        Ref::filter_map(textures, |textures| textures.get(id))
+            .map_err(|textures| textures.error(id))
+    }
+
+    pub fn try_texture_mut(
+        &self,
+        id: imgui::TextureId,
+    ) -> Result<RefMut<'_, Texture>, TextureError> {
+        self.try_register_pending_textures();
+        let textures = self
+            .textures
+            .try_borrow_mut()
+            .map_err(|_| TextureError::Borrowed(id))?;
+        RefMut::filter_map(textures, |textures| textures.get_mut(id))
+            .map_err(|textures| textures.error(id))
+    }
+
+    // Textures may already be borrowed here, in which case pending ones just wait a little longer
+    fn try_register_pending_textures(&self) {
+        if let Ok(mut textures) = self.textures.try_borrow_mut() {
+            if textures.has_pending() {
+                textures.register_pending();
+            }
+        }
+    }
+
+    fn registered_textures(&self) -> Ref<'_, TextureRegistry> {
+        self.try_register_pending_textures();
+        self.textures.borrow()
+    }
+
+    #[inline]
+    #[track_caller]
+    pub fn texture(&self, id: imgui::TextureId) -> Ref<'_, Texture> {
+        self.try_texture(id).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    #[inline]
+    #[track_caller]
+    pub fn texture_mut(&self, id: imgui::TextureId) -> RefMut<'_, Texture> {
+        self.try_texture_mut(id)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    #[inline]
+    pub fn missing_texture_fallback(&self) -> MissingTextureFallback {
+        self.missing_texture_fallback
+    }
+
+    #[inline]
+    pub fn set_missing_texture_fallback(&mut self, value: MissingTextureFallback) {
+        self.missing_texture_fallback = value;
+    }
+
+    #[inline]
+    pub fn log_missing_textures(&self) -> bool {
+        self.log_missing_textures
     }
 
     #[inline]
-    pub fn texture(&self, id: imgui::TextureId) -> Ref<Texture> {
-        Ref::map(self.textures.borrow(), |textures| &textures[&id])
+    pub fn set_log_missing_textures(&mut self, value: bool) {
+        self.log_missing_textures = value;
     }
 
     #[inline]
-    pub fn texture_mut(&self, id: imgui::TextureId) -> RefMut<Texture> {
-        RefMut::map(self.textures.borrow_mut(), |textures| {
-            textures.get_mut(&id).unwrap()
+    pub fn missing_texture_ids(&self) -> impl Iterator<Item = imgui::TextureId> + '_ {
+        self.missing_texture_ids.iter().copied()
+    }
+
+    fn checkerboard_texture(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> &Texture {
+        self.checkerboard_texture.get_or_init(|| {
+            const SIZE: u32 = 8;
+            let texture = self.create_owned_texture(
+                Some("imgui missing texture".into()),
+                TextureDescriptor {
+                    width: SIZE,
+                    height: SIZE,
+                    depth_or_array_layers: 1,
+                    mip_level_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: wgpu::TextureFormat::Rgba8Unorm,
+                    usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+                },
+                SamplerDescriptor::default(),
+            );
+            let data = (0..SIZE * SIZE)
+                .flat_map(|i| {
+                    if (i % SIZE + i / SIZE).is_multiple_of(2) {
+                        [255, 0, 255, 255]
+                    } else {
+                        [0, 0, 0, 255]
+                    }
+                })
+                .collect::<Vec<u8>>();
+            texture.set_data(device, queue, &data, TextureSetRange::default());
+            Texture::Owned(texture)
         })
     }
 
+    pub fn generate_mipmaps(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &OwnedTexture,
+    ) {
+        let generator = self
+            .mipmap_generator
+            .get_or_init(|| MipmapGenerator::new(device));
+        texture.generate_mipmaps(device, encoder, generator);
+    }
+
     pub fn reload_fonts(
         &mut self,
         device: &wgpu::Device,
@@ -684,8 +3091,8 @@ impl Renderer {
         imgui: &mut imgui::Context,
     ) {
         let font_tex_id = imgui.fonts().tex_id;
-        if font_tex_id.id() != 0 {
-            self.textures.get_mut().remove(&font_tex_id);
+        if font_tex_id != FONT_TEXTURE_ID {
+            self.textures.get_mut().remove(font_tex_id);
         }
         let fonts = imgui.fonts();
         let font_atlas = fonts.build_rgba32_texture();
@@ -694,7 +3101,9 @@ impl Renderer {
             TextureDescriptor {
                 width: font_atlas.width,
                 height: font_atlas.height,
+                depth_or_array_layers: 1,
                 mip_level_count: 1,
+                dimension: wgpu::TextureDimension::D2,
                 format: wgpu::TextureFormat::Rgba8Unorm,
                 usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
             },
@@ -706,9 +3115,10 @@ impl Renderer {
         );
         font_texture.set_data(device, queue, font_atlas.data, TextureSetRange::default());
         fonts.clear_tex_data();
+        fonts.tex_id = FONT_TEXTURE_ID;
         self.textures
             .get_mut()
-            .insert(font_tex_id, Texture::Owned(font_texture));
+            .set_font_texture(Texture::Owned(font_texture));
     }
 
     pub fn render(
@@ -719,6 +3129,17 @@ impl Renderer {
         frame: &wgpu::TextureView,
         draw_data: &imgui::DrawData,
     ) {
+        self.register_pending_textures();
+        self.remove_dropped_textures();
+        // Only IDs that were still missing last frame stay reported, which keeps the set small and
+        // reports an ID again if it goes missing after being found
+        let missing_texture_ids = &self.missing_texture_ids;
+        self.reported_missing_texture_ids
+            .retain(|id| missing_texture_ids.contains(id));
+        self.missing_texture_ids.clear();
+        self.flush_textures(device, queue);
+        self.rebuild_derived_views(device);
+
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: None,
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
@@ -796,7 +3217,6 @@ impl Renderer {
         queue.write_buffer(vtx_buffer, 0, &vtx);
         queue.write_buffer(idx_buffer, 0, &idx);
 
-        render_pass.set_pipeline(&self.pipeline);
         render_pass.set_index_buffer(
             idx_buffer.slice(..),
             if size_of::<imgui::DrawIdx>() == 2 {
@@ -826,16 +3246,36 @@ impl Renderer {
         }
         render_pass.set_bind_group(0, &self.view_bind_group, &[]);
 
-        let textures = self.textures.get_mut();
+        let textures = self.textures.borrow();
+        let mut cur_pipeline_key = None;
         let mut vtx_base = 0;
         let mut idx_base = 0;
         for draw_list in draw_data.draw_lists() {
             for cmd in draw_list.commands() {
                 match cmd {
                     imgui::DrawCmd::Elements { count, cmd_params } => {
-                        let texture = match textures.get(&cmd_params.texture_id) {
+                        let texture = match textures.get(cmd_params.texture_id) {
                             Some(texture) => texture,
-                            None => continue,
+                            None => {
+                                let id = cmd_params.texture_id;
+                                self.missing_texture_ids.insert(id);
+                                if self.log_missing_textures
+                                    && self.reported_missing_texture_ids.insert(id)
+                                {
+                                    log::warn!("{} while rendering", textures.error(id));
+                                }
+                                let fallback = match self.missing_texture_fallback {
+                                    MissingTextureFallback::Skip => None,
+                                    MissingTextureFallback::Checkerboard => {
+                                        Some(self.checkerboard_texture(device, queue))
+                                    }
+                                    MissingTextureFallback::Texture(id) => textures.get(id),
+                                };
+                                match fallback {
+                                    Some(texture) => texture,
+                                    None => continue,
+                                }
+                            }
                         };
 
                         render_pass.set_vertex_buffer(0, vtx_buffer.slice(..));
@@ -874,11 +3314,19 @@ impl Renderer {
                             scissor_size[1],
                         );
 
-                        render_pass.set_bind_group(
-                            1,
-                            texture.bind_group(device, &self.texture_bind_group_layout),
-                            &[],
-                        );
+                        let sample_type = texture.sample_type(device);
+                        let pipeline_key =
+                            (sample_type, texture.display().view_dimension(sample_type));
+                        if cur_pipeline_key != Some(pipeline_key) {
+                            let (sample_type, view_dimension) = pipeline_key;
+                            render_pass.set_pipeline(
+                                &self
+                                    .texture_pipeline(device, sample_type, view_dimension)
+                                    .pipeline,
+                            );
+                            cur_pipeline_key = Some(pipeline_key);
+                        }
+                        render_pass.set_bind_group(1, texture.bind_group(device, self), &[]);
 
                         let idx_start = idx_base + cmd_params.idx_offset;
                         render_pass.draw_indexed(
@@ -889,7 +3337,7 @@ impl Renderer {
                     }
 
                     imgui::DrawCmd::ResetRenderState => {
-                        render_pass.set_pipeline(&self.pipeline);
+                        cur_pipeline_key = None;
                         render_pass.set_index_buffer(
                             idx_buffer.slice(..),
                             if size_of::<imgui::DrawIdx>() == 2 {
@@ -912,3 +3360,496 @@ impl Renderer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texture(format: wgpu::TextureFormat, width: u32, height: u32) -> OwnedTexture {
+        OwnedTexture::new(
+            None,
+            TextureDescriptor {
+                width,
+                height,
+                mip_level_count: 4,
+                format,
+                ..Default::default()
+            },
+            SamplerDescriptor::default(),
+        )
+    }
+
+    fn extent(width: u32, height: u32) -> wgpu::Extent3d {
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        }
+    }
+
+    #[test]
+    fn copy_layout_of_full_levels() {
+        let texture = texture(wgpu::TextureFormat::Rgba8Unorm, 20, 8);
+        let layout = texture.copy_layout(&TextureSetRange::default());
+        assert_eq!(layout, Ok((extent(20, 8), 80, 8)));
+        let range = TextureSetRange {
+            mip_level: 3,
+            ..Default::default()
+        };
+        assert_eq!(texture.copy_layout(&range), Ok((extent(2, 1), 8, 1)));
+        let range = TextureSetRange {
+            mip_level: 4,
+            ..Default::default()
+        };
+        assert_eq!(
+            texture.copy_layout(&range),
+            Err(TextureDataError::MipLevelOutOfRange {
+                mip_level: 4,
+                mip_level_count: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn copy_layout_of_sub_ranges() {
+        let texture = texture(wgpu::TextureFormat::R16Uint, 16, 16);
+        let range = TextureSetRange {
+            x: 3,
+            y: 5,
+            width: Some(7),
+            height: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(texture.copy_layout(&range), Ok((extent(7, 1), 14, 1)));
+        let range = TextureSetRange {
+            x: 10,
+            y: 12,
+            ..Default::default()
+        };
+        assert_eq!(texture.copy_layout(&range), Ok((extent(6, 4), 12, 4)));
+        let range = TextureSetRange {
+            x: 10,
+            width: Some(7),
+            ..Default::default()
+        };
+        assert_eq!(
+            texture.copy_layout(&range),
+            Err(TextureDataError::RangeOutOfBounds {
+                mip_level: 0,
+                width: 16,
+                height: 16,
+            })
+        );
+    }
+
+    #[test]
+    fn copy_layout_of_compressed_formats() {
+        let texture = texture(wgpu::TextureFormat::Bc1RgbaUnorm, 16, 12);
+        let layout = texture.copy_layout(&TextureSetRange::default());
+        assert_eq!(layout, Ok((extent(16, 12), 32, 3)));
+        // Levels smaller than a block still cover a whole one
+        let range = TextureSetRange {
+            mip_level: 3,
+            ..Default::default()
+        };
+        assert_eq!(texture.copy_layout(&range), Ok((extent(4, 4), 8, 1)));
+        let range = TextureSetRange {
+            x: 4,
+            y: 8,
+            width: Some(8),
+            height: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(texture.copy_layout(&range), Ok((extent(8, 4), 16, 1)));
+        let range = TextureSetRange {
+            x: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            texture.copy_layout(&range),
+            Err(TextureDataError::UnalignedRange {
+                format: wgpu::TextureFormat::Bc1RgbaUnorm,
+                block_width: 4,
+                block_height: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn copy_layout_of_volume_slices() {
+        let mut texture = texture(wgpu::TextureFormat::Rgba8Unorm, 8, 8);
+        texture.set_texture_desc(TextureDescriptor {
+            depth_or_array_layers: 4,
+            mip_level_count: 2,
+            dimension: wgpu::TextureDimension::D3,
+            ..*texture.texture_desc()
+        });
+        let range = TextureSetRange {
+            mip_level: 1,
+            layer: 1,
+            ..Default::default()
+        };
+        assert_eq!(texture.copy_layout(&range), Ok((extent(4, 4), 16, 4)));
+        let range = TextureSetRange {
+            mip_level: 1,
+            layer: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            texture.copy_layout(&range),
+            Err(TextureDataError::LayerOutOfRange {
+                layer: 2,
+                layer_count: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn memory_size_of_formats() {
+        let desc = |format, width, height, mip_level_count| TextureDescriptor {
+            width,
+            height,
+            mip_level_count,
+            format,
+            ..Default::default()
+        };
+        let rgba = desc(wgpu::TextureFormat::Rgba8Unorm, 64, 64, 7);
+        assert_eq!(rgba.memory_size(), Some(21844));
+        let bc1 = desc(wgpu::TextureFormat::Bc1RgbaUnorm, 6, 6, 1);
+        assert_eq!(bc1.memory_size(), Some(32));
+        let depth_stencil = desc(wgpu::TextureFormat::Depth32FloatStencil8, 4, 4, 1);
+        assert_eq!(depth_stencil.memory_size(), Some(80));
+        let depth = desc(wgpu::TextureFormat::Depth24PlusStencil8, 4, 4, 1);
+        assert_eq!(depth.memory_size(), None);
+        let nv12 = desc(wgpu::TextureFormat::NV12, 4, 4, 1);
+        assert_eq!(nv12.memory_size(), None);
+    }
+
+    #[test]
+    fn validate_data_uses_the_level_row_pitch() {
+        let texture = texture(wgpu::TextureFormat::Rgba8Unorm, 16, 16);
+        let range = TextureSetRange {
+            x: 4,
+            width: Some(4),
+            height: Some(3),
+            ..Default::default()
+        };
+        let (_, row_size, rows) = texture.copy_layout(&range).unwrap();
+        // Rows are read at the pitch of the whole level, but the last one only needs its texels
+        let required = 64 * 2 + 16;
+        assert_eq!(
+            texture.validate_data(&range, row_size, rows, required),
+            Ok(())
+        );
+        assert_eq!(
+            texture.validate_data(&range, row_size, rows, required - 1),
+            Err(TextureDataError::DataTooShort {
+                required: required as u64,
+                len: required - 1,
+            })
+        );
+    }
+
+    #[test]
+    fn validate_data_of_single_rows_and_offsets() {
+        let texture = texture(wgpu::TextureFormat::Rgba8Unorm, 16, 16);
+        let range = TextureSetRange {
+            y: 7,
+            height: Some(1),
+            offset: 8,
+            ..Default::default()
+        };
+        let (_, row_size, rows) = texture.copy_layout(&range).unwrap();
+        assert_eq!(texture.validate_data(&range, row_size, rows, 72), Ok(()));
+        assert!(texture.validate_data(&range, row_size, rows, 71).is_err());
+        // Data written from the CPU can start anywhere
+        let range = TextureSetRange { offset: 6, ..range };
+        assert_eq!(texture.validate_data(&range, row_size, rows, 70), Ok(()));
+        assert!(texture.validate_data(&range, row_size, rows, 69).is_err());
+    }
+
+    #[test]
+    fn validate_buffer_offsets() {
+        let rgba = texture(wgpu::TextureFormat::Rgba8Unorm, 16, 16);
+        assert_eq!(rgba.validate_buffer_offset(8), Ok(()));
+        assert_eq!(
+            rgba.validate_buffer_offset(6),
+            Err(TextureDataError::UnalignedOffset {
+                offset: 6,
+                alignment: 4,
+            })
+        );
+        // Depth and stencil aspects need 4-byte aligned offsets even with smaller texels
+        let depth = texture(wgpu::TextureFormat::Depth16Unorm, 16, 16);
+        assert_eq!(
+            depth.validate_buffer_offset(2),
+            Err(TextureDataError::UnalignedOffset {
+                offset: 2,
+                alignment: 4,
+            })
+        );
+        assert_eq!(depth.validate_buffer_offset(4), Ok(()));
+    }
+
+    #[test]
+    fn validate_data_of_compressed_formats() {
+        let texture = texture(wgpu::TextureFormat::Bc3RgbaUnorm, 16, 16);
+        let range = TextureSetRange {
+            mip_level: 1,
+            ..Default::default()
+        };
+        let (_, row_size, rows) = texture.copy_layout(&range).unwrap();
+        assert_eq!((row_size, rows), (32, 2));
+        assert_eq!(texture.validate_data(&range, row_size, rows, 64), Ok(()));
+        assert!(texture.validate_data(&range, row_size, rows, 63).is_err());
+        let range = TextureSetRange {
+            width: Some(4),
+            ..range
+        };
+        let (_, row_size, rows) = texture.copy_layout(&range).unwrap();
+        assert_eq!(texture.validate_data(&range, row_size, rows, 48), Ok(()));
+        assert!(texture.validate_data(&range, row_size, rows, 47).is_err());
+    }
+
+    #[test]
+    fn buffer_copy_pitch_of_single_and_multiple_rows() {
+        // A single row of 10 RGBA8 texels is copied without a pitch, even though 40 is unaligned
+        assert_eq!(buffer_copy_bytes_per_row(40, 40, 1), Ok(None));
+        assert_eq!(buffer_copy_bytes_per_row(40, 40, 0), Ok(None));
+        assert_eq!(buffer_copy_bytes_per_row(256, 40, 4), Ok(Some(256)));
+        assert_eq!(
+            buffer_copy_bytes_per_row(40, 40, 4),
+            Err(TextureDataError::UnalignedBytesPerRow(40))
+        );
+        assert_eq!(
+            buffer_copy_bytes_per_row(256, 512, 2),
+            Err(TextureDataError::BytesPerRowTooSmall {
+                bytes_per_row: 256,
+                row_size: 512,
+            })
+        );
+    }
+
+    #[test]
+    fn read_layout_of_empty_ranges() {
+        let mut texture = texture(wgpu::TextureFormat::Rgba8Unorm, 16, 16);
+        let range = TextureSetRange {
+            width: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            texture.read_layout(&range),
+            Err(TextureDataError::MissingUsage(
+                wgpu::TextureUsages::COPY_SRC
+            ))
+        );
+        texture.set_texture_desc(TextureDescriptor {
+            usage: wgpu::TextureUsages::COPY_SRC,
+            ..*texture.texture_desc()
+        });
+        assert_eq!(texture.read_layout(&range), Ok(None));
+        let range = TextureSetRange {
+            height: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(texture.read_layout(&range), Ok(None));
+        let range = TextureSetRange {
+            height: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            texture.read_layout(&range),
+            Ok(Some((extent(16, 2), 64, 2)))
+        );
+    }
+
+    #[test]
+    fn validate_view_range_against_the_descriptor() {
+        let mut texture = texture(wgpu::TextureFormat::Rgba8Unorm, 16, 16);
+        let range = TextureViewRange {
+            base_mip_level: 2,
+            mip_level_count: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(texture.validate_view_range(&range), Ok(()));
+        let range = TextureViewRange {
+            base_mip_level: 3,
+            ..range
+        };
+        assert_eq!(
+            texture.validate_view_range(&range),
+            Err(TextureViewRangeError::MipLevelsOutOfRange {
+                base_mip_level: 3,
+                mip_level_count: Some(2),
+                texture_mip_level_count: 4,
+            })
+        );
+        let range = TextureViewRange {
+            base_mip_level: 4,
+            mip_level_count: None,
+            ..range
+        };
+        assert!(texture.validate_view_range(&range).is_err());
+        let range = TextureViewRange {
+            base_array_layer: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            texture.try_set_view_range(range),
+            Err(TextureViewRangeError::LayerOutOfRange {
+                layer: 1,
+                layer_count: 1,
+            })
+        );
+        let range = TextureViewRange {
+            aspect: wgpu::TextureAspect::DepthOnly,
+            ..Default::default()
+        };
+        assert_eq!(
+            texture.try_set_view_range(range),
+            Err(TextureViewRangeError::MissingAspect {
+                aspect: wgpu::TextureAspect::DepthOnly,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+            })
+        );
+
+        // Layers select the displayed slice of 3D textures
+        texture.set_texture_desc(TextureDescriptor {
+            depth_or_array_layers: 8,
+            dimension: wgpu::TextureDimension::D3,
+            ..*texture.texture_desc()
+        });
+        assert_eq!(texture.try_set_layer(7), Ok(()));
+        assert_eq!(texture.display(), TextureDisplay::Slice(7));
+        assert!(texture.try_set_layer(8).is_err());
+        // Shrinking the texture resets a range it no longer has
+        texture.set_texture_desc(TextureDescriptor {
+            depth_or_array_layers: 4,
+            ..*texture.texture_desc()
+        });
+        assert_eq!(texture.view_range(), &TextureViewRange::default());
+    }
+
+    #[test]
+    fn depth_display_ranges() {
+        let display = |near, far, linearize| DepthDisplay {
+            near,
+            far,
+            linearize,
+        };
+        assert_eq!(display(0.0, 1.0, false).validate(), Ok(()));
+        assert_eq!(display(1.0, 0.0, false).validate(), Ok(()));
+        assert_eq!(
+            display(0.5, 0.5, false).validate(),
+            Err(DepthDisplayError::EmptyRange(0.5))
+        );
+        assert_eq!(display(0.1, 100.0, true).validate(), Ok(()));
+        // Reversed-Z
+        assert_eq!(display(100.0, 0.1, true).validate(), Ok(()));
+        assert_eq!(
+            display(2.0, 2.0, true).validate(),
+            Err(DepthDisplayError::EmptyRange(2.0))
+        );
+        assert_eq!(
+            display(0.0, 100.0, true).validate(),
+            Err(DepthDisplayError::InvalidLinearRange {
+                near: 0.0,
+                far: 100.0,
+            })
+        );
+    }
+
+    #[test]
+    fn shadow_copy_follows_the_texture_desc() {
+        let mut texture = texture(wgpu::TextureFormat::Rgba8Unorm, 16, 16);
+        assert_eq!(texture.set_shadow_copy(true), Ok(()));
+        assert_eq!(texture.shadow_data_mut(u32::MAX), None);
+        texture.resize(32, 8);
+        assert!(texture.has_shadow_copy());
+        assert_eq!(
+            texture.shadow_data_mut(0).map(|data| data.len()),
+            Some(32 * 8 * 4)
+        );
+        texture.set_texture_desc(TextureDescriptor {
+            format: wgpu::TextureFormat::Bc1RgbaUnorm,
+            ..*texture.texture_desc()
+        });
+        assert!(!texture.has_shadow_copy());
+        assert_eq!(
+            texture.set_shadow_copy(true),
+            Err(TextureDataError::UnsupportedFormat(
+                wgpu::TextureFormat::Bc1RgbaUnorm
+            ))
+        );
+    }
+
+    #[test]
+    fn resolve_display_of_volumes() {
+        let mut texture = texture(wgpu::TextureFormat::Rgba8Unorm, 8, 8);
+        let range = TextureViewRange {
+            base_array_layer: 3,
+            ..Default::default()
+        };
+        let display = texture.resolve_display(&range, TextureDisplay::Layer);
+        assert_eq!(display, TextureDisplay::Layer);
+        assert_eq!(
+            texture.validate_display(TextureDisplay::Slice(3)),
+            Err(TextureDisplayError::NotVolume(wgpu::TextureDimension::D2))
+        );
+        texture.set_texture_desc(TextureDescriptor {
+            depth_or_array_layers: 4,
+            dimension: wgpu::TextureDimension::D3,
+            ..*texture.texture_desc()
+        });
+        let display = texture.resolve_display(&range, TextureDisplay::Layer);
+        assert_eq!(display, TextureDisplay::Slice(3));
+        assert_eq!(texture.validate_display(display), Ok(()));
+    }
+
+    #[test]
+    fn view_displays_of_depth_sample_types() {
+        let depth = wgpu::TextureSampleType::Depth;
+        assert_eq!(
+            TextureView::validate_display(depth, TextureDisplay::Slice(0)),
+            Err(TextureDisplayError::DepthVolume)
+        );
+        assert_eq!(
+            TextureView::validate_display(depth, TextureDisplay::CubeCross),
+            Ok(())
+        );
+        assert_eq!(
+            TextureView::validate_display(FILTERABLE_FLOAT, TextureDisplay::Slice(0)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn layer_view_ranges() {
+        let mut texture = texture(wgpu::TextureFormat::Rgba8Unorm, 8, 8);
+        texture.set_texture_desc(TextureDescriptor {
+            depth_or_array_layers: 2,
+            ..*texture.texture_desc()
+        });
+        let range = texture
+            .layer_view_range(1)
+            .map(|range| range.base_array_layer);
+        assert_eq!(range, Ok(1));
+        assert_eq!(
+            texture.layer_view_range(2),
+            Err(TextureViewRangeError::LayerOutOfRange {
+                layer: 2,
+                layer_count: 2,
+            })
+        );
+        texture.set_texture_desc(TextureDescriptor {
+            dimension: wgpu::TextureDimension::D3,
+            ..*texture.texture_desc()
+        });
+        assert_eq!(
+            texture.layer_view_range(0),
+            Err(TextureViewRangeError::NotLayered(
+                wgpu::TextureDimension::D3
+            ))
+        );
+    }
+}
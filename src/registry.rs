@@ -0,0 +1,445 @@
+use crate::{
+    OwnedTexture, SamplerDescriptor, Texture, TextureDescriptor, TextureHandle, TextureView,
+    TextureViewError, FILTERABLE_FLOAT,
+};
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+const INDEX_BITS: u32 = usize::BITS / 2;
+const INDEX_MASK: usize = (1 << INDEX_BITS) - 1;
+const MAX_GENERATION: usize = usize::MAX >> INDEX_BITS;
+
+// Slot 0 is reserved for the font atlas so that its ID stays `TextureId(0)`.
+pub(crate) const FONT_TEXTURE_ID: imgui::TextureId = imgui::TextureId::new(0);
+
+struct Slot {
+    generation: usize,
+    texture: Option<Texture>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Lookup {
+    Present,
+    Missing,
+    Stale,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureError {
+    Missing(imgui::TextureId),
+    Stale(imgui::TextureId),
+    NotOwned(imgui::TextureId),
+    Borrowed(imgui::TextureId),
+    InvalidView(imgui::TextureId, TextureViewError),
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureError::Missing(id) => write!(f, "texture {id:?} does not exist"),
+            TextureError::Stale(id) => write!(f, "texture {id:?} is stale: it has been removed"),
+            TextureError::NotOwned(id) => {
+                write!(f, "texture {id:?} is a view, not an owned texture")
+            }
+            TextureError::InvalidView(id, err) => {
+                write!(f, "can't create a view of texture {id:?}: {err}")
+            }
+            TextureError::Borrowed(id) => write!(
+                f,
+                "texture {id:?} can't be accessed while the textures are mutably borrowed"
+            ),
+        }
+    }
+}
+
+impl Error for TextureError {}
+
+// The queues only ever hold plain lists, so a panic elsewhere can't leave them inconsistent
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+struct SlotAllocator {
+    slot_count: usize,
+    free_slots: Vec<(usize, usize)>,
+}
+
+// IDs are handed out here so that other threads can register textures without touching the
+// slots, which only the renderer's thread owns.
+pub(crate) struct SharedRegistry {
+    allocator: Mutex<SlotAllocator>,
+    pending_textures: Mutex<Vec<(imgui::TextureId, Texture)>>,
+    dropped_textures: Mutex<Vec<imgui::TextureId>>,
+}
+
+impl SharedRegistry {
+    fn reserve(&self) -> imgui::TextureId {
+        let mut allocator = lock(&self.allocator);
+        let (index, generation) = match allocator.free_slots.pop() {
+            Some(slot) => slot,
+            None => {
+                assert!(
+                    allocator.slot_count <= INDEX_MASK,
+                    "too many textures registered at once"
+                );
+                allocator.slot_count += 1;
+                (allocator.slot_count - 1, 0)
+            }
+        };
+        TextureRegistry::pack(index, generation)
+    }
+
+    fn add_texture(&self, texture: Texture) -> imgui::TextureId {
+        let id = self.reserve();
+        lock(&self.pending_textures).push((id, texture));
+        id
+    }
+
+    pub(crate) fn drop_texture(&self, id: imgui::TextureId) {
+        lock(&self.dropped_textures).push(id);
+    }
+}
+
+// On wasm, wgpu types are only `Send` and `Sync` with its `fragile-send-sync-non-atomic-wasm`
+// feature
+#[cfg(not(target_arch = "wasm32"))]
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<TextureRegistryHandle>();
+    assert_send_sync::<TextureHandle>();
+};
+
+#[derive(Clone)]
+pub struct TextureRegistryHandle {
+    shared: Arc<SharedRegistry>,
+}
+
+impl TextureRegistryHandle {
+    #[inline]
+    pub fn create_owned_texture(
+        &self,
+        label: Option<Cow<'static, str>>,
+        texture_desc: TextureDescriptor,
+        sampler_desc: SamplerDescriptor,
+    ) -> OwnedTexture {
+        OwnedTexture::new(label, texture_desc, sampler_desc)
+    }
+
+    #[inline]
+    pub fn create_texture_view(
+        &self,
+        label: Option<Cow<'static, str>>,
+        texture_view: wgpu::TextureView,
+        sampler_desc: SamplerDescriptor,
+    ) -> TextureView {
+        TextureView::new(label, texture_view, FILTERABLE_FLOAT, sampler_desc)
+    }
+
+    #[inline]
+    pub fn create_texture_view_with_sample_type(
+        &self,
+        label: Option<Cow<'static, str>>,
+        texture_view: wgpu::TextureView,
+        sample_type: wgpu::TextureSampleType,
+        sampler_desc: SamplerDescriptor,
+    ) -> TextureView {
+        TextureView::new(label, texture_view, sample_type, sampler_desc)
+    }
+
+    #[inline]
+    pub fn add_texture(&self, texture: Texture) -> imgui::TextureId {
+        self.shared.add_texture(texture)
+    }
+
+    #[inline]
+    pub fn add_texture_with_handle(&self, texture: Texture) -> TextureHandle {
+        let id = self.add_texture(texture);
+        self.texture_handle(id)
+    }
+
+    #[inline]
+    pub fn texture_handle(&self, id: imgui::TextureId) -> TextureHandle {
+        TextureHandle {
+            id,
+            registry: Arc::downgrade(&self.shared),
+        }
+    }
+
+    #[inline]
+    pub fn remove_texture(&self, id: imgui::TextureId) {
+        self.shared.drop_texture(id);
+    }
+}
+
+pub(crate) struct TextureRegistry {
+    shared: Arc<SharedRegistry>,
+    slots: Vec<Slot>,
+    len: usize,
+}
+
+impl TextureRegistry {
+    #[must_use]
+    pub(crate) fn new() -> Self {
+        TextureRegistry {
+            shared: Arc::new(SharedRegistry {
+                allocator: Mutex::new(SlotAllocator {
+                    slot_count: 1,
+                    free_slots: Vec::new(),
+                }),
+                pending_textures: Mutex::new(Vec::new()),
+                dropped_textures: Mutex::new(Vec::new()),
+            }),
+            slots: vec![Slot {
+                generation: 0,
+                texture: None,
+            }],
+            len: 0,
+        }
+    }
+
+    pub(crate) fn handle(&self) -> TextureRegistryHandle {
+        TextureRegistryHandle {
+            shared: self.shared.clone(),
+        }
+    }
+
+    #[inline]
+    fn pack(index: usize, generation: usize) -> imgui::TextureId {
+        imgui::TextureId::new(generation << INDEX_BITS | index)
+    }
+
+    #[inline]
+    fn unpack(id: imgui::TextureId) -> (usize, usize) {
+        (id.id() & INDEX_MASK, id.id() >> INDEX_BITS)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Texture> {
+        self.slots.iter().filter_map(|slot| slot.texture.as_ref())
+    }
+
+    pub(crate) fn iter_with_ids(&self) -> impl Iterator<Item = (imgui::TextureId, &Texture)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            Some((Self::pack(index, slot.generation), slot.texture.as_ref()?))
+        })
+    }
+
+    pub(crate) fn insert(&mut self, texture: Texture) -> imgui::TextureId {
+        let id = self.shared.reserve();
+        self.place(id, texture);
+        id
+    }
+
+    fn place(&mut self, id: imgui::TextureId, texture: Texture) {
+        let (index, generation) = Self::unpack(id);
+        if index >= self.slots.len() {
+            self.slots.resize_with(index + 1, || Slot {
+                generation: 0,
+                texture: None,
+            });
+        }
+        let slot = &mut self.slots[index];
+        slot.generation = generation;
+        slot.texture = Some(texture);
+        self.len += 1;
+    }
+
+    pub(crate) fn has_pending(&self) -> bool {
+        !lock(&self.shared.pending_textures).is_empty()
+    }
+
+    pub(crate) fn register_pending(&mut self) {
+        let pending_textures = std::mem::take(&mut *lock(&self.shared.pending_textures));
+        for (id, texture) in pending_textures {
+            self.place(id, texture);
+        }
+    }
+
+    pub(crate) fn has_dropped(&self) -> bool {
+        !lock(&self.shared.dropped_textures).is_empty()
+    }
+
+    pub(crate) fn remove_dropped(&mut self) {
+        // Textures can be registered and dropped before this thread sees them
+        self.register_pending();
+        let dropped_textures = std::mem::take(&mut *lock(&self.shared.dropped_textures));
+        for id in dropped_textures {
+            self.remove(id);
+        }
+    }
+
+    pub(crate) fn set_font_texture(&mut self, texture: Texture) {
+        let slot = &mut self.slots[0];
+        if slot.texture.is_none() {
+            self.len += 1;
+        }
+        slot.texture = Some(texture);
+    }
+
+    pub(crate) fn remove(&mut self, id: imgui::TextureId) -> Option<Texture> {
+        let (index, generation) = Self::unpack(id);
+        let slot = self
+            .slots
+            .get_mut(index)
+            .filter(|slot| slot.generation == generation)?;
+        let texture = slot.texture.take()?;
+        self.len -= 1;
+        if index != 0 {
+            // Slots whose generation would wrap are retired so old IDs can never alias them.
+            if slot.generation < MAX_GENERATION {
+                slot.generation += 1;
+                lock(&self.shared.allocator)
+                    .free_slots
+                    .push((index, slot.generation));
+            }
+        }
+        Some(texture)
+    }
+
+    pub(crate) fn lookup(&self, id: imgui::TextureId) -> Lookup {
+        let (index, generation) = Self::unpack(id);
+        match self.slots.get(index) {
+            Some(slot) if slot.generation != generation => {
+                if generation < slot.generation {
+                    Lookup::Stale
+                } else {
+                    Lookup::Missing
+                }
+            }
+            Some(Slot {
+                texture: Some(_), ..
+            }) => Lookup::Present,
+            _ => Lookup::Missing,
+        }
+    }
+
+    pub(crate) fn error(&self, id: imgui::TextureId) -> TextureError {
+        match self.lookup(id) {
+            Lookup::Stale => TextureError::Stale(id),
+            _ => TextureError::Missing(id),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, id: imgui::TextureId) -> Option<&Texture> {
+        let (index, generation) = Self::unpack(id);
+        self.slots
+            .get(index)
+            .filter(|slot| slot.generation == generation)?
+            .texture
+            .as_ref()
+    }
+
+    #[inline]
+    pub(crate) fn get_mut(&mut self, id: imgui::TextureId) -> Option<&mut Texture> {
+        let (index, generation) = Self::unpack(id);
+        self.slots
+            .get_mut(index)
+            .filter(|slot| slot.generation == generation)?
+            .texture
+            .as_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texture() -> Texture {
+        Texture::Owned(OwnedTexture::new(
+            None,
+            TextureDescriptor::default(),
+            SamplerDescriptor::default(),
+        ))
+    }
+
+    #[test]
+    fn remove_bumps_generation() {
+        let mut registry = TextureRegistry::new();
+        let id = registry.insert(texture());
+        assert_eq!(TextureRegistry::unpack(id), (1, 0));
+        assert!(registry.remove(id).is_some());
+        assert!(registry.remove(id).is_none());
+        assert!(registry.get(id).is_none());
+
+        let new_id = registry.insert(texture());
+        assert_eq!(TextureRegistry::unpack(new_id), (1, 1));
+        assert!(registry.get(id).is_none());
+        assert!(registry.get(new_id).is_some());
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn lookup_distinguishes_stale_and_missing() {
+        let mut registry = TextureRegistry::new();
+        let id = registry.insert(texture());
+        assert_eq!(registry.lookup(id), Lookup::Present);
+        registry.remove(id);
+        assert_eq!(registry.lookup(id), Lookup::Stale);
+        assert_eq!(registry.error(id), TextureError::Stale(id));
+
+        let (index, generation) = TextureRegistry::unpack(id);
+        let future_id = TextureRegistry::pack(index, generation + 2);
+        assert_eq!(registry.lookup(future_id), Lookup::Missing);
+        let unused_id = TextureRegistry::pack(42, 0);
+        assert_eq!(registry.lookup(unused_id), Lookup::Missing);
+        assert_eq!(registry.error(unused_id), TextureError::Missing(unused_id));
+    }
+
+    #[test]
+    fn slot_at_max_generation_is_retired() {
+        let mut registry = TextureRegistry::new();
+        let id = registry.insert(texture());
+        registry.remove(id);
+        let (index, _) = TextureRegistry::unpack(id);
+        // Takes the freed slot out of the allocator, as placing it directly bypasses it
+        let reserved_id = registry.shared.reserve();
+        assert_eq!(TextureRegistry::unpack(reserved_id).0, index);
+
+        let last_id = TextureRegistry::pack(index, MAX_GENERATION);
+        registry.place(last_id, texture());
+        assert!(registry.remove(last_id).is_some());
+        assert_eq!(registry.lookup(last_id), Lookup::Missing);
+        assert_ne!(TextureRegistry::unpack(registry.insert(texture())).0, index);
+    }
+
+    #[test]
+    fn font_slot_is_never_recycled() {
+        let mut registry = TextureRegistry::new();
+        registry.set_font_texture(texture());
+        assert_eq!(registry.lookup(FONT_TEXTURE_ID), Lookup::Present);
+        assert!(registry.remove(FONT_TEXTURE_ID).is_some());
+        assert_eq!(registry.len(), 0);
+
+        let id = registry.insert(texture());
+        assert_ne!(TextureRegistry::unpack(id).0, 0);
+        registry.set_font_texture(texture());
+        assert_eq!(registry.lookup(FONT_TEXTURE_ID), Lookup::Present);
+    }
+
+    #[test]
+    fn pending_textures_are_registered_before_being_dropped() {
+        let mut registry = TextureRegistry::new();
+        let handle = registry.handle();
+        let kept_id = handle.add_texture(texture());
+        let dropped_id = handle.add_texture(texture());
+        assert!(registry.has_pending());
+        assert_eq!(registry.lookup(kept_id), Lookup::Missing);
+
+        registry.shared.drop_texture(dropped_id);
+        assert!(registry.has_dropped());
+        registry.remove_dropped();
+        assert!(!registry.has_pending());
+        assert!(!registry.has_dropped());
+        assert_eq!(registry.lookup(kept_id), Lookup::Present);
+        assert_eq!(registry.lookup(dropped_id), Lookup::Stale);
+        assert_eq!(registry.len(), 1);
+    }
+}
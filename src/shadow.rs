@@ -0,0 +1,183 @@
+use crate::TextureSetRange;
+
+const MAX_DIRTY_RECTS: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct DirtyRect {
+    pub(crate) layer: u32,
+    pub(crate) x: u32,
+    pub(crate) y: u32,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+impl DirtyRect {
+    pub(crate) fn new(range: &TextureSetRange, extent: wgpu::Extent3d) -> Self {
+        DirtyRect {
+            layer: range.layer,
+            x: range.x,
+            y: range.y,
+            width: extent.width,
+            height: extent.height,
+        }
+    }
+
+    fn touches(&self, other: &DirtyRect) -> bool {
+        self.layer == other.layer
+            && self.x <= other.x + other.width
+            && other.x <= self.x + self.width
+            && self.y <= other.y + other.height
+            && other.y <= self.y + self.height
+    }
+
+    fn union(&self, other: &DirtyRect) -> DirtyRect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        DirtyRect {
+            layer: self.layer,
+            x,
+            y,
+            width: (self.x + self.width).max(other.x + other.width) - x,
+            height: (self.y + self.height).max(other.y + other.height) - y,
+        }
+    }
+}
+
+pub(crate) struct ShadowCopy {
+    pub(crate) data: Vec<u8>,
+    pub(crate) bytes_per_row: u32,
+    pub(crate) rows_per_layer: u32,
+    dirty_rects: Vec<DirtyRect>,
+}
+
+impl ShadowCopy {
+    #[must_use]
+    pub(crate) fn new(bytes_per_row: u32, rows_per_layer: u32, layers: u32) -> Self {
+        ShadowCopy {
+            data: vec![0; bytes_per_row as usize * rows_per_layer as usize * layers as usize],
+            bytes_per_row,
+            rows_per_layer,
+            dirty_rects: Vec::new(),
+        }
+    }
+
+    pub(crate) fn layer_offset(&self, layer: u32) -> usize {
+        layer as usize * self.rows_per_layer as usize * self.bytes_per_row as usize
+    }
+
+    pub(crate) fn write(
+        &mut self,
+        rect: DirtyRect,
+        texel_size: u32,
+        src: &[u8],
+        src_offset: u64,
+        src_bytes_per_row: u32,
+    ) {
+        let row_size = (rect.width * texel_size) as usize;
+        let dst_offset = self.layer_offset(rect.layer)
+            + rect.y as usize * self.bytes_per_row as usize
+            + (rect.x * texel_size) as usize;
+        for row in 0..rect.height as usize {
+            let src_start = src_offset as usize + row * src_bytes_per_row as usize;
+            let dst_start = dst_offset + row * self.bytes_per_row as usize;
+            self.data[dst_start..dst_start + row_size]
+                .copy_from_slice(&src[src_start..src_start + row_size]);
+        }
+    }
+
+    pub(crate) fn dirty_rect_count(&self) -> usize {
+        self.dirty_rects.len()
+    }
+
+    pub(crate) fn mark_dirty(&mut self, mut rect: DirtyRect) {
+        if rect.width == 0 || rect.height == 0 {
+            return;
+        }
+        while let Some(index) = self.dirty_rects.iter().position(|r| r.touches(&rect)) {
+            rect = rect.union(&self.dirty_rects.swap_remove(index));
+        }
+        self.dirty_rects.push(rect);
+
+        if self.dirty_rects.len() > MAX_DIRTY_RECTS {
+            // Too many scattered updates; fall back to one bounding rect per layer.
+            let mut merged: Vec<DirtyRect> = Vec::new();
+            for rect in self.dirty_rects.drain(..) {
+                match merged.iter_mut().find(|r| r.layer == rect.layer) {
+                    Some(r) => *r = r.union(&rect),
+                    None => merged.push(rect),
+                }
+            }
+            self.dirty_rects = merged;
+        }
+    }
+
+    pub(crate) fn take_dirty_rects(&mut self) -> Vec<DirtyRect> {
+        std::mem::take(&mut self.dirty_rects)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(layer: u32, x: u32, y: u32, width: u32, height: u32) -> DirtyRect {
+        DirtyRect {
+            layer,
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn touches_includes_adjacent_rects() {
+        let a = rect(0, 0, 0, 4, 4);
+        assert!(a.touches(&rect(0, 2, 2, 4, 4)));
+        assert!(a.touches(&rect(0, 4, 0, 4, 4)));
+        assert!(a.touches(&rect(0, 0, 4, 4, 4)));
+        assert!(!a.touches(&rect(0, 5, 0, 4, 4)));
+        assert!(!a.touches(&rect(0, 0, 5, 4, 4)));
+        assert!(!a.touches(&rect(1, 0, 0, 4, 4)));
+    }
+
+    #[test]
+    fn union_covers_both_rects() {
+        let a = rect(2, 1, 6, 3, 2);
+        let b = rect(2, 5, 2, 2, 3);
+        assert_eq!(a.union(&b), rect(2, 1, 2, 6, 6));
+        assert_eq!(b.union(&a), rect(2, 1, 2, 6, 6));
+        assert_eq!(a.union(&a), a);
+    }
+
+    #[test]
+    fn touching_rects_are_merged() {
+        let mut shadow = ShadowCopy::new(64, 16, 2);
+        shadow.mark_dirty(rect(0, 0, 0, 4, 4));
+        shadow.mark_dirty(rect(0, 8, 0, 4, 4));
+        shadow.mark_dirty(rect(1, 4, 0, 4, 4));
+        shadow.mark_dirty(rect(0, 0, 0, 0, 4));
+        assert_eq!(shadow.dirty_rect_count(), 3);
+
+        // Bridges the two rects of layer 0, which then merge into one
+        shadow.mark_dirty(rect(0, 4, 0, 4, 1));
+        let mut dirty_rects = shadow.take_dirty_rects();
+        dirty_rects.sort_by_key(|r| r.layer);
+        assert_eq!(dirty_rects, [rect(0, 0, 0, 12, 4), rect(1, 4, 0, 4, 4)]);
+        assert_eq!(shadow.dirty_rect_count(), 0);
+    }
+
+    #[test]
+    fn too_many_rects_fall_back_to_bounding_rects() {
+        let mut shadow = ShadowCopy::new(1024, 8, 2);
+        for i in 0..MAX_DIRTY_RECTS as u32 {
+            shadow.mark_dirty(rect(0, i * 4, 2, 1, 1));
+        }
+        assert_eq!(shadow.dirty_rect_count(), MAX_DIRTY_RECTS);
+
+        shadow.mark_dirty(rect(1, 10, 5, 2, 2));
+        let mut dirty_rects = shadow.take_dirty_rects();
+        dirty_rects.sort_by_key(|r| r.layer);
+        assert_eq!(dirty_rects, [rect(0, 0, 2, 125, 1), rect(1, 10, 5, 2, 2)]);
+    }
+}
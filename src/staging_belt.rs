@@ -0,0 +1,137 @@
+use std::sync::{mpsc, Arc};
+
+struct Chunk {
+    buffer: Arc<wgpu::Buffer>,
+    size: u64,
+    offset: u64,
+}
+
+pub struct TextureStagingBelt {
+    chunk_size: u64,
+    active_chunks: Vec<Chunk>,
+    closed_chunks: Vec<Chunk>,
+    free_chunks: Vec<Chunk>,
+    sender: mpsc::Sender<Chunk>,
+    receiver: mpsc::Receiver<Chunk>,
+}
+
+impl TextureStagingBelt {
+    #[must_use]
+    pub fn new(chunk_size: u64) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        TextureStagingBelt {
+            chunk_size,
+            active_chunks: Vec::new(),
+            closed_chunks: Vec::new(),
+            free_chunks: Vec::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn chunk_size(&self) -> u64 {
+        self.chunk_size
+    }
+
+    fn allocate(&mut self, device: &wgpu::Device, size: u64) -> (Arc<wgpu::Buffer>, u64) {
+        let chunk_index = if let Some(index) = self
+            .active_chunks
+            .iter()
+            .position(|chunk| chunk.offset + size <= chunk.size)
+        {
+            index
+        } else {
+            let chunk =
+                if let Some(index) = self.free_chunks.iter().position(|chunk| size <= chunk.size) {
+                    self.free_chunks.swap_remove(index)
+                } else {
+                    Chunk {
+                        buffer: Arc::new(device.create_buffer(&wgpu::BufferDescriptor {
+                            label: Some("imgui texture staging belt"),
+                            size: self.chunk_size.max(size),
+                            usage: wgpu::BufferUsages::MAP_WRITE | wgpu::BufferUsages::COPY_SRC,
+                            mapped_at_creation: true,
+                        })),
+                        size: self.chunk_size.max(size),
+                        offset: 0,
+                    }
+                };
+            self.active_chunks.push(chunk);
+            self.active_chunks.len() - 1
+        };
+
+        let chunk = &mut self.active_chunks[chunk_index];
+        let offset = chunk.offset;
+        chunk.offset = (offset + size).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64);
+        (Arc::clone(&chunk.buffer), offset)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn write_texture(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: wgpu::ImageCopyTexture,
+        data: &[u8],
+        data_layout: wgpu::ImageDataLayout,
+        row_size: u32,
+        rows: u32,
+        size: wgpu::Extent3d,
+    ) {
+        let padded_row_size = row_size.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let (buffer, offset) = self.allocate(device, padded_row_size as u64 * rows as u64);
+        {
+            let mut mapped_range = buffer
+                .slice(offset..offset + padded_row_size as u64 * rows as u64)
+                .get_mapped_range_mut();
+            let src_bytes_per_row = data_layout.bytes_per_row.unwrap_or(row_size) as usize;
+            for (row, dst) in mapped_range
+                .chunks_exact_mut(padded_row_size as usize)
+                .enumerate()
+            {
+                let src_start = data_layout.offset as usize + row * src_bytes_per_row;
+                dst[..row_size as usize]
+                    .copy_from_slice(&data[src_start..src_start + row_size as usize]);
+            }
+        }
+        encoder.copy_buffer_to_texture(
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset,
+                    bytes_per_row: Some(padded_row_size),
+                    rows_per_image: Some(rows),
+                },
+            },
+            texture,
+            size,
+        );
+    }
+
+    pub fn finish(&mut self) {
+        for chunk in self.active_chunks.drain(..) {
+            chunk.buffer.unmap();
+            self.closed_chunks.push(chunk);
+        }
+    }
+
+    pub fn recall(&mut self) {
+        while let Ok(mut chunk) = self.receiver.try_recv() {
+            chunk.offset = 0;
+            self.free_chunks.push(chunk);
+        }
+
+        for chunk in self.closed_chunks.drain(..) {
+            let sender = self.sender.clone();
+            let buffer = Arc::clone(&chunk.buffer);
+            buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Write, move |result| {
+                    // A chunk that failed to map can't be written to again, so it's dropped
+                    if result.is_ok() {
+                        let _ = sender.send(chunk);
+                    }
+                });
+        }
+    }
+}